@@ -0,0 +1,135 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+pub enum Mode {
+    Interactive,
+    Report,
+    /// Adds the entries of a CSV file to the sheet and saves it.
+    Import(PathBuf),
+    /// Writes the day as an iCalendar file.
+    ExportIcs(PathBuf),
+    /// Sums up the dated sheets of the last seven days.
+    Week,
+    /// Prints what saving the sheet would write, without saving it.
+    Check,
+    /// Prints one line for a status bar, per `status_format`.
+    Status,
+}
+
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+    Markdown,
+    JsonLines,
+}
+
+pub struct Options {
+    pub mode: Mode,
+    pub format: Format,
+    pub billable: bool,
+    /// `STDIN_FILE` stands for reading the sheet from stdin.
+    pub file: Option<PathBuf>,
+}
+
+/// The file argument that reads the sheet from stdin instead.
+pub const STDIN_FILE: &str = "-";
+
+pub fn reads_stdin(file: Option<&Path>) -> bool {
+    file == Some(Path::new(STDIN_FILE))
+}
+
+pub fn parse() -> Result<Options, String> {
+    let mut mode = Mode::Interactive;
+    let mut format = Format::Text;
+    let mut billable = false;
+    let mut file = None;
+    let mut args = env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some("--report") => mode = Mode::Report,
+            Some("--week") => mode = Mode::Week,
+            Some("--check") => mode = Mode::Check,
+            Some("--status") => mode = Mode::Status,
+            Some("--import-csv") => match args.next() {
+                Some(csv) => mode = Mode::Import(PathBuf::from(csv)),
+                None => return Err("--import-csv needs a CSV file".to_string()),
+            },
+            Some("--export-ics") => match args.next() {
+                Some(out) => mode = Mode::ExportIcs(PathBuf::from(out)),
+                None => return Err("--export-ics needs an output file".to_string()),
+            },
+            Some("--json") => format = Format::Json,
+            Some("--billable") => billable = true,
+            Some("--export-csv") => {
+                mode = Mode::Report;
+                format = Format::Csv;
+            }
+            Some("--export-jsonl") => {
+                mode = Mode::Report;
+                format = Format::JsonLines;
+            }
+            Some("--export-md") => {
+                mode = Mode::Report;
+                format = Format::Markdown;
+            }
+            Some(flag) if flag.starts_with("--") => {
+                return Err(format!("unknown option: {}", flag));
+            }
+            _ if file.is_some() => {
+                return Err(format!("unexpected argument: {}", arg.to_string_lossy()));
+            }
+            _ => file = Some(PathBuf::from(arg)),
+        }
+    }
+
+    if reads_stdin(file.as_deref()) {
+        match mode {
+            // There is nothing to save to, so the TUI has no use for it.
+            Mode::Interactive => mode = Mode::Report,
+            Mode::Import(_) => {
+                return Err("--import-csv saves the sheet and can’t read it from stdin".to_string())
+            }
+            _ => {}
+        }
+    }
+    if let (Mode::Week, Some(_)) = (&mode, &file) {
+        return Err("--week reads the dated sheets and takes no file".to_string());
+    }
+    if let (
+        Mode::Interactive
+        | Mode::Import(_)
+        | Mode::ExportIcs(_)
+        | Mode::Week
+        | Mode::Check
+        | Mode::Status,
+        Format::Json,
+    ) = (&mode, &format)
+    {
+        return Err("--json only works together with --report".to_string());
+    }
+    if billable && !matches!((&mode, &format), (Mode::Report, Format::Text)) {
+        return Err("--billable only works together with a plain --report".to_string());
+    }
+
+    if let Some(path) = &file {
+        match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => {
+                return Err(format!(
+                    "directory {} does not exist",
+                    parent.to_string_lossy()
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(Options {
+        mode,
+        format,
+        billable,
+        file,
+    })
+}
@@ -0,0 +1,56 @@
+use std::{
+    env,
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+/// The clipboard tools to try, in order, with their arguments.
+fn candidates() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        vec![("pbcopy", &[])]
+    } else if cfg!(windows) {
+        vec![("clip", &[])]
+    } else {
+        let mut tools: Vec<(&'static str, &'static [&'static str])> = Vec::new();
+        if env::var_os("WAYLAND_DISPLAY").is_some() {
+            tools.push(("wl-copy", &[]));
+        }
+        if env::var_os("DISPLAY").is_some() {
+            tools.push(("xclip", &["-selection", "clipboard"]));
+            tools.push(("xsel", &["--clipboard", "--input"]));
+        }
+        // WSL has no display of its own but can reach the Windows clipboard.
+        tools.push(("clip.exe", &[]));
+        tools
+    }
+}
+
+/**
+ * Puts `text` on the system clipboard through the first tool that is installed.
+ * There is no clipboard crate in the dependencies, and these tools are what a terminal
+ * session has anyway; without a display (e.g. over SSH) none of them can work.
+ */
+pub fn copy(text: &str) -> Result<(), String> {
+    for (program, args) in candidates() {
+        match pipe_into(program, args, text) {
+            Ok(true) => return Ok(()),
+            Ok(false) => return Err(format!("{} failed", program)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(format!("{}: {}", program, err)),
+        }
+    }
+    Err("no clipboard available".to_string())
+}
+
+fn pipe_into(program: &str, args: &[&str], text: &str) -> io::Result<bool> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    Ok(child.wait()?.success())
+}
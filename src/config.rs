@@ -0,0 +1,572 @@
+use crate::timesheet;
+use ratatui::style::Color;
+use serde::{Deserialize, Deserializer};
+use std::{env, fs, io, path::PathBuf, str::FromStr};
+use time::UtcOffset;
+
+/// Settings from `tracc/config.json` in the OS config directory.
+/// Every field is optional; missing ones keep the built-in defaults.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Entry texts that count as a pause, compared case-insensitively.
+    pub pause_texts: Vec<String>,
+    /// The summary bucket all pause texts are collapsed into.
+    pub pause_label: String,
+    /// Pause texts for paid breaks, summed up separately; they count as pauses like `pause_texts`.
+    pub paid_pause_texts: Vec<String>,
+    /// Whether paid breaks count towards the working total.
+    pub paid_pauses_count_as_work: bool,
+    /// Working time per day in minutes, given as `H:MM` in the file. 0 disables it.
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub daily_target: i64,
+    /// Backups kept per sheet; one is made before the first save of a session.
+    pub backups: usize,
+    /// Whether clearing an entry's text offers to delete it; otherwise empty text is refused.
+    pub remove_empty_entries: bool,
+    /// The step `-` and `+` shift by and round to, in minutes.
+    pub shift_step: i64,
+    /// Where the `YYYY/MM/DD.json` sheets live, instead of `tracc/timesheets` in the data directory.
+    pub timesheet_dir: Option<PathBuf>,
+    /// Extra named sheets, e.g. one per client, switched with Tab next to the default one.
+    pub sheets: Vec<String>,
+    /// Record new entries to the second and show durations as `H:MM:SS`.
+    pub seconds: bool,
+    /// Pad durations to two digits of hours (`03:05`) in the summary and the exports.
+    pub padded_durations: bool,
+    /// Tasks the summary lists first, in this order; the others follow alphabetically.
+    pub task_order: Vec<String>,
+    /// Whether typed and shifted times may lie after the current time.
+    pub allow_future_entries: bool,
+    /// The zone "now" is taken in, for new entries and the running task.
+    pub timezone: Timezone,
+    /// How times of day are shown, e.g. `%H:%M` or `%I:%M %p`; see `format_time_of_day`.
+    pub time_format: String,
+    /// The widths of the list and the summary column.
+    pub columns: Columns,
+    /// Whether the summary sits next to or below the list.
+    pub orientation: Orientation,
+    /// Colors and selection marker of the time list.
+    pub theme: Theme,
+    /// Append a summary line for the open day to `daily_log_path` on quit, if it was changed.
+    pub daily_log: bool,
+    /// Where the daily log goes instead of `daily.log` next to the sheets.
+    pub daily_log_path: Option<PathBuf>,
+    /// Minutes without input after which tracc offers to backfill a pause. 0 disables it.
+    pub idle_minutes: u64,
+    /// Minutes after which the running last entry is flagged as possibly forgotten. 0 disables it.
+    pub long_running_minutes: u64,
+    /// Days the sparkline of the summary pane covers, up to the open one. 0 hides it.
+    pub sparkline_days: u16,
+    /// Interval lengths of the Pomodoro timer started with `T`.
+    pub pomodoro: Pomodoro,
+    /// Whether `/` also finds entries containing the term's letters in order, best match first.
+    pub fuzzy_search: bool,
+    /// The `--status` line while a task runs; see `TimeSheet::status_line` for the placeholders.
+    pub status_format: String,
+    /// The `--status` line while nothing runs.
+    pub status_idle_format: String,
+    /// Keys that replace the built-in ones of the main actions.
+    pub keys: Keys,
+}
+
+/// `"local"` follows the system; fixed offsets are written like `"+02:00"`, `"-0530"` or `"UTC"`.
+/// IANA names such as `Europe/Berlin` would need a time zone database, which tracc doesn’t ship.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Timezone {
+    #[default]
+    Local,
+    Fixed(UtcOffset),
+}
+
+impl<'de> Deserialize<'de> for Timezone {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        parse_timezone(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+pub fn parse_timezone(value: &str) -> Result<Timezone, String> {
+    let value = value.trim();
+    match value.to_lowercase().as_str() {
+        "local" => return Ok(Timezone::Local),
+        "utc" | "z" => return Ok(Timezone::Fixed(UtcOffset::UTC)),
+        _ => {}
+    }
+    let invalid = || {
+        format!(
+            "invalid timezone: {} (expected \"local\", \"UTC\" or an offset like \"+02:00\")",
+            value
+        )
+    };
+    let (sign, rest) = match value.chars().next() {
+        Some('+') => (1, &value[1..]),
+        Some('-') => (-1, &value[1..]),
+        _ => return Err(invalid()),
+    };
+    let digits = match rest.split_once(':') {
+        Some((hours, minutes)) if hours.len() == 2 && minutes.len() == 2 => {
+            hours.to_string() + minutes
+        }
+        Some(_) => return Err(invalid()),
+        None => rest.to_string(),
+    };
+    if !matches!(digits.len(), 2 | 4) || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let hours: i8 = digits[..2].parse().map_err(|_| invalid())?;
+    let minutes: i8 = match &digits[2..] {
+        "" => 0,
+        minutes => minutes.parse().map_err(|_| invalid())?,
+    };
+    UtcOffset::from_hms(sign * hours, sign * minutes, 0)
+        .map(Timezone::Fixed)
+        .map_err(|_| invalid())
+}
+
+/// Colors are names like `"lightgreen"`, indexes like `"42"`, or `"#rrggbb"`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    #[serde(deserialize_with = "deserialize_color")]
+    pub foreground: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub background: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub highlight_foreground: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub highlight_background: Color,
+    pub highlight_symbol: String,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub pause: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub warning: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            foreground: Color::Reset,
+            background: Color::Reset,
+            highlight_foreground: Color::LightGreen,
+            highlight_background: Color::Reset,
+            highlight_symbol: ">".to_string(),
+            pause: Color::DarkGray,
+            warning: Color::Yellow,
+        }
+    }
+}
+
+/// The longest Pomodoro interval; the timer adds them to `Instant`s, which can overflow.
+const MAX_POMODORO_MINUTES: u64 = 24 * 60;
+
+/// All lengths are minutes; every `long_break_every`th break is a long one.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Pomodoro {
+    pub work: u64,
+    pub short_break: u64,
+    pub long_break: u64,
+    pub long_break_every: u32,
+}
+
+impl Default for Pomodoro {
+    fn default() -> Self {
+        Self {
+            work: 25,
+            short_break: 5,
+            long_break: 15,
+            long_break_every: 4,
+        }
+    }
+}
+
+/// The plain keys of the other normal- and visual-mode commands, which can't be remapped;
+/// keep in sync with `handle_normal_input` and `handle_visual_input`.
+const FIXED_KEYS: &str = "JKGgyYOaiAI ?<>~=pDPTtxcCmsB.S%:V/nN@bHLurR";
+
+/// Single characters like `"e"`; unmapped actions keep their built-in key.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keys {
+    pub next: Option<char>,
+    pub prev: Option<char>,
+    pub shift_up: Option<char>,
+    pub shift_down: Option<char>,
+    pub delete: Option<char>,
+    pub new: Option<char>,
+    pub save: Option<char>,
+    pub quit: Option<char>,
+}
+
+impl Keys {
+    /// Every action with its configured and its built-in key.
+    fn actions(&self) -> [(&'static str, Option<char>, char); 8] {
+        [
+            ("next", self.next, 'j'),
+            ("prev", self.prev, 'k'),
+            ("shift_up", self.shift_up, '+'),
+            ("shift_down", self.shift_down, '-'),
+            ("delete", self.delete, 'd'),
+            ("new", self.new, 'o'),
+            ("save", self.save, 'w'),
+            ("quit", self.quit, 'q'),
+        ]
+    }
+
+    /// The built-in key of the action `key` is mapped to, or `key` itself.
+    /// A built-in key keeps working as long as no other action takes it.
+    pub fn translate(&self, key: char) -> char {
+        self.actions()
+            .iter()
+            .find(|(_, mapped, _)| *mapped == Some(key))
+            .map_or(key, |(_, _, builtin)| *builtin)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let actions = self.actions();
+        for (index, (action, mapped, _)) in actions.iter().enumerate() {
+            let Some(key) = mapped else { continue };
+            if let Some((other, ..)) = actions[..index].iter().find(|(_, m, _)| m == mapped) {
+                return Err(format!(
+                    "keys: {} and {} are both mapped to {:?}",
+                    other, action, key
+                ));
+            }
+            if FIXED_KEYS.contains(*key) {
+                return Err(format!(
+                    "keys: {:?} for {} is already a built-in key",
+                    key, action
+                ));
+            }
+            // The key would shadow an action that still relies on it.
+            if let Some((other, ..)) = actions
+                .iter()
+                .find(|(other, m, builtin)| builtin == key && m.is_none() && other != action)
+            {
+                return Err(format!(
+                    "keys: {:?} for {} is the key of {}, which has no other",
+                    key, action, other
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    /// Vertical in terminals narrower than 80 columns, horizontal otherwise.
+    Auto,
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "unit", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Columns {
+    /// Shares of the terminal width; they have to add up to 100.
+    Percent { list: u16, summary: u16 },
+    /// Fixed widths in terminal cells.
+    Length { list: u16, summary: u16 },
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            pause_texts: ["pause", "lunch", "break", "end"]
+                .iter()
+                .map(|text| text.to_string())
+                .collect(),
+            pause_label: "pause".to_string(),
+            paid_pause_texts: Vec::new(),
+            paid_pauses_count_as_work: false,
+            daily_target: 8 * 60,
+            backups: 5,
+            remove_empty_entries: true,
+            shift_step: 5,
+            timesheet_dir: None,
+            sheets: Vec::new(),
+            seconds: false,
+            padded_durations: false,
+            task_order: Vec::new(),
+            allow_future_entries: false,
+            timezone: Timezone::Local,
+            time_format: "%H:%M".to_string(),
+            columns: Columns::Percent {
+                list: 60,
+                summary: 40,
+            },
+            orientation: Orientation::Auto,
+            theme: Theme::default(),
+            daily_log: false,
+            daily_log_path: None,
+            idle_minutes: 0,
+            long_running_minutes: 3 * 60,
+            sparkline_days: 14,
+            pomodoro: Pomodoro::default(),
+            fuzzy_search: false,
+            status_format: "{task} {running} | total {total}".to_string(),
+            status_idle_format: "idle | total {total}".to_string(),
+            keys: Keys::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Self, String> {
+        let path = config_dir().join("tracc").join("config.json");
+        let file = match fs::File::open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(format!("can’t read {}: {}", path.display(), err)),
+        };
+        let mut config: Self = serde_json::from_reader(io::BufReader::new(file))
+            .map_err(|err| format!("invalid config {}: {}", path.display(), err))?;
+        for text in config
+            .pause_texts
+            .iter_mut()
+            .chain(config.paid_pause_texts.iter_mut())
+        {
+            *text = text.trim().to_lowercase();
+        }
+        config
+            .validate()
+            .map_err(|err| format!("invalid config {}: {}", path.display(), err))?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        timesheet::format_time_of_day(0, &self.time_format)
+            .map_err(|err| format!("time_format: {}", err))?;
+        if self.shift_step < 1 {
+            return Err("shift_step must be at least 1".to_string());
+        }
+        if self.pause_label.trim().is_empty() {
+            return Err("pause_label must not be empty".to_string());
+        }
+        if self.daily_target < 0 {
+            return Err("daily_target must not be negative".to_string());
+        }
+        for (index, sheet) in self.sheets.iter().enumerate() {
+            // Digits-only names would collide with the year directories of the default sheet.
+            if sheet.is_empty()
+                || sheet.contains(['/', '\\'])
+                || sheet.starts_with('.')
+                || sheet.chars().all(|c| c.is_ascii_digit())
+            {
+                return Err(format!("invalid sheet name: {:?}", sheet));
+            }
+            if self.sheets[..index].contains(sheet) {
+                return Err(format!("duplicate sheet name: {:?}", sheet));
+            }
+        }
+        let Pomodoro {
+            work,
+            short_break,
+            long_break,
+            long_break_every,
+        } = self.pomodoro;
+        if work == 0 || short_break == 0 || long_break == 0 || long_break_every == 0 {
+            return Err("pomodoro intervals must be at least 1".to_string());
+        }
+        if [work, short_break, long_break]
+            .iter()
+            .any(|&minutes| minutes > MAX_POMODORO_MINUTES)
+        {
+            return Err("pomodoro intervals must be at most 24 hours".to_string());
+        }
+        if let Columns::Percent { list, summary } = self.columns {
+            if u32::from(list) + u32::from(summary) != 100 {
+                return Err("percent columns must add up to 100".to_string());
+            }
+        }
+        self.keys.validate()
+    }
+
+    pub fn is_pause(&self, text: &str) -> bool {
+        let text = text.trim().to_lowercase();
+        text == self.pause_label.to_lowercase()
+            || self.pause_texts.contains(&text)
+            || self.paid_pause_texts.contains(&text)
+    }
+
+    pub fn is_paid_pause(&self, text: &str) -> bool {
+        self.paid_pause_texts.contains(&text.trim().to_lowercase())
+    }
+}
+
+fn deserialize_duration<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    timesheet::parse_minutes(&value).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_color<'de, D>(deserializer: D) -> Result<Color, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    Color::from_str(&value)
+        .map_err(|_| serde::de::Error::custom(format!("invalid color: {}", value)))
+}
+
+#[cfg(windows)]
+fn config_dir() -> PathBuf {
+    env::var_os("APPDATA")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+#[cfg(target_os = "macos")]
+fn config_dir() -> PathBuf {
+    env::var_os("HOME")
+        .map(|home| {
+            PathBuf::from(home)
+                .join("Library")
+                .join("Application Support")
+        })
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+#[cfg(all(not(windows), not(target_os = "macos")))]
+fn config_dir() -> PathBuf {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_timezone, Config, Timezone};
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(Config::default().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_empty_pomodoro_intervals() {
+        let config: Config =
+            serde_json::from_str(r#"{"pomodoro": {"work": 50, "short_break": 10}}"#).unwrap();
+        assert_eq!(config.pomodoro.long_break, 15);
+        assert!(config.validate().is_ok());
+        let config: Config = serde_json::from_str(r#"{"pomodoro": {"work": 0}}"#).unwrap();
+        assert!(config.validate().is_err());
+        let config: Config =
+            serde_json::from_str(r#"{"pomodoro": {"long_break": 18446744073709551615}}"#).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn rejects_a_zero_shift_step() {
+        let config: Config = serde_json::from_str(r#"{"shift_step": 0}"#).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn percent_columns_must_add_up_to_100() {
+        let config: Config =
+            serde_json::from_str(r#"{"columns": {"unit": "percent", "list": 50, "summary": 40}}"#)
+                .unwrap();
+        assert!(config.validate().is_err());
+        let config: Config =
+            serde_json::from_str(r#"{"columns": {"unit": "length", "list": 50, "summary": 40}}"#)
+                .unwrap();
+        assert!(config.validate().is_ok());
+        let config: Config = serde_json::from_str(
+            r#"{"columns": {"unit": "percent", "list": 65535, "summary": 1}}"#,
+        )
+        .unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn parses_named_indexed_and_rgb_colors() {
+        use ratatui::style::Color;
+        let config: Config = serde_json::from_str(
+            r##"{"theme": {"foreground": "black", "background": "231", "highlight_foreground": "#005f87"}}"##,
+        )
+        .unwrap();
+        assert_eq!(config.theme.foreground, Color::Black);
+        assert_eq!(config.theme.background, Color::Indexed(231));
+        assert_eq!(config.theme.highlight_foreground, Color::Rgb(0, 0x5f, 0x87));
+        assert!(serde_json::from_str::<Config>(r#"{"theme": {"foreground": "nope"}}"#).is_err());
+    }
+
+    #[test]
+    fn rejects_a_bad_time_format() {
+        let config: Config = serde_json::from_str(r#"{"time_format": "%H:%X"}"#).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn parses_timezones() {
+        use time::UtcOffset;
+        assert_eq!(parse_timezone("local"), Ok(Timezone::Local));
+        assert_eq!(parse_timezone("UTC"), Ok(Timezone::Fixed(UtcOffset::UTC)));
+        assert_eq!(
+            parse_timezone("+02:00"),
+            Ok(Timezone::Fixed(UtcOffset::from_hms(2, 0, 0).unwrap()))
+        );
+        assert_eq!(
+            parse_timezone("-0530"),
+            Ok(Timezone::Fixed(UtcOffset::from_hms(-5, -30, 0).unwrap()))
+        );
+        assert_eq!(
+            parse_timezone("+09"),
+            Ok(Timezone::Fixed(UtcOffset::from_hms(9, 0, 0).unwrap()))
+        );
+        assert!(parse_timezone("Europe/Berlin").is_err());
+        assert!(parse_timezone("+2:0").is_err());
+        assert!(parse_timezone("+02:75").is_err());
+    }
+
+    #[test]
+    fn sheet_names_must_be_unique_directory_names() {
+        for sheets in [r#"["a", "a"]"#, r#"["../x"]"#, r#"["2026"]"#, r#"[""]"#] {
+            let config: Config =
+                serde_json::from_str(&format!(r#"{{"sheets": {}}}"#, sheets)).unwrap();
+            assert!(config.validate().is_err(), "{}", sheets);
+        }
+        let config: Config =
+            serde_json::from_str(r#"{"sheets": ["acme", "side-project"]}"#).unwrap();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn remaps_keys_and_rejects_conflicts() {
+        let config: Config =
+            serde_json::from_str(r#"{"keys": {"next": "e", "prev": "h", "new": "k"}}"#).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.keys.translate('e'), 'j');
+        assert_eq!(config.keys.translate('k'), 'o');
+        assert_eq!(config.keys.translate('j'), 'j');
+        assert_eq!(config.keys.translate('x'), 'x');
+        for keys in [
+            r#"{"next": "e", "prev": "e"}"#,
+            r#"{"new": "q"}"#,
+            r#"{"next": "n"}"#,
+            r#"{"delete": "u"}"#,
+            r#"{"new": "y"}"#,
+            r#"{"quit": "/"}"#,
+            r#"{"save": "V"}"#,
+        ] {
+            let config: Config = serde_json::from_str(&format!(r#"{{"keys": {}}}"#, keys)).unwrap();
+            assert!(config.validate().is_err(), "{}", keys);
+        }
+        assert!(serde_json::from_str::<Config>(r#"{"keys": {"next": "nn"}}"#).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(serde_json::from_str::<Config>(r#"{"shift_stpe": 15}"#).is_err());
+    }
+}
@@ -0,0 +1,68 @@
+use std::{error, fmt, io, path::PathBuf};
+
+/// Why a sheet couldn’t be loaded.
+#[derive(Debug)]
+pub enum TraccError {
+    /// There is no file yet, which callers usually treat as an empty sheet.
+    NotFound(PathBuf),
+    /// The file exists but couldn’t be read.
+    Io(PathBuf, io::Error),
+    /// The file was read but isn’t a valid sheet.
+    Parse(PathBuf, serde_json::Error),
+}
+
+impl TraccError {
+    /// Sorts an error of reading `path` into not-found and the other IO errors.
+    pub fn io(path: PathBuf, err: io::Error) -> Self {
+        if err.kind() == io::ErrorKind::NotFound {
+            Self::NotFound(path)
+        } else {
+            Self::Io(path, err)
+        }
+    }
+
+    /// Like `io`, for an error of parsing `path`, which may have come from reading it.
+    pub fn json(path: PathBuf, err: serde_json::Error) -> Self {
+        if err.is_io() {
+            Self::io(path, err.into())
+        } else {
+            Self::Parse(path, err)
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::NotFound(_))
+    }
+}
+
+impl fmt::Display for TraccError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotFound(path) => write!(f, "can’t read {}: file not found", path.display()),
+            Self::Io(path, err) => write!(f, "can’t read {}: {}", path.display(), err),
+            Self::Parse(path, err) => write!(f, "invalid sheet {}: {}", path.display(), err),
+        }
+    }
+}
+
+impl error::Error for TraccError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::NotFound(_) => None,
+            Self::Io(_, err) => Some(err),
+            Self::Parse(_, err) => Some(err),
+        }
+    }
+}
+
+/// Lets the TUI, which deals in `io::Result`, pass the error on with `?`.
+impl From<TraccError> for io::Error {
+    fn from(err: TraccError) -> Self {
+        let kind = match &err {
+            TraccError::NotFound(_) => io::ErrorKind::NotFound,
+            TraccError::Io(_, err) => err.kind(),
+            TraccError::Parse(..) => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, err.to_string())
+    }
+}
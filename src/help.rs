@@ -5,18 +5,52 @@ use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 
 const BINDINGS: &[(&str, &str)] = &[
     ("j / k", "move selection down / up"),
+    ("click / wheel", "select an item / move the selection"),
+    ("Alt+j / Alt+k", "move item down / up"),
     ("J / K", "go to next / previous day"),
+    ("Tab / Shift+Tab", "switch to the next / previous sheet"),
     ("gg / G", "jump to first / last item"),
+    ("Ctrl+d / Ctrl+u", "move half a page down / up"),
+    ("Ctrl+f / Ctrl+b", "move a full page down / up"),
     ("gt", "go to today"),
     ("gd", "load a specific day"),
     ("y / p", "yank / paste current item"),
-    ("o", "insert new item"),
+    ("P", "paste item at its original time"),
+    ("D", "duplicate the current item now"),
+    ("b", "mark the current item billable / not billable"),
+    ("@", "set / clear the project of the current item"),
+    ("Y", "copy the summary to the clipboard"),
+    ("o / O", "insert new item now / at a typed time"),
     ("a / i", "edit item text (append / insert)"),
     ("A / I", "edit item time (append / insert)"),
     ("r / R", "replace item text / time"),
     ("d", "delete current item"),
+    ("m", "merge following items with the same task"),
+    ("x", "split item at a typed time"),
+    ("c / C", "rename all items by text / by task"),
+    ("s", "stop tracking (insert \"end\" now)"),
+    (".", "resume the last task now"),
+    ("B", "start / end a break now"),
     ("- / +", "shift time to previous / next 5-minute mark"),
+    ("< / >", "shift item and all later ones back / forward"),
+    ("=", "set time to now"),
+    ("~", "round all items to the nearest 5-minute mark"),
+    ("/", "search items (empty to clear)"),
+    ("V", "select a range for d / < / > (Esc cancels)"),
+    (":", "run a command (:w, :q, :wq, :export, :target)"),
+    ("n / N", "jump to the next / previous match"),
+    ("S", "sort summary by name / duration"),
+    ("%", "toggle task percentages"),
+    ("L", "toggle item durations in the list"),
+    ("H", "toggle the worked time per hour"),
     ("u / Ctrl+r", "undo / redo"),
+    ("T", "start / stop the Pomodoro timer"),
+    ("t", "focus the todos / the times"),
+    ("todos: o / a", "add a todo / edit the selected one"),
+    ("todos: Space / d", "toggle done / delete"),
+    ("edit: Tab", "complete the text from other items"),
+    ("edit: Ctrl+o", "save and insert a new item now"),
+    ("w", "save the sheet"),
     ("q", "quit"),
     ("?", "toggle this help"),
 ];
@@ -0,0 +1,26 @@
+use crate::config::Config;
+use crate::timesheet::TimeSheet;
+use std::{fs, io, path::Path, rc::Rc};
+
+/// Adds the rows of `csv` to `file`, or to today’s sheet, and saves it.
+/// A backup is made first, like before the first change of a session.
+pub fn run(csv: &Path, file: Option<&Path>, config: Config) -> Result<(), String> {
+    let config = Rc::new(config);
+    let mut sheet = match file {
+        Some(path) => TimeSheet::open_file(path.to_path_buf(), None, &config),
+        None => TimeSheet::open(TimeSheet::current_date(), &config),
+    }
+    .map_err(|err| err.to_string())?;
+    let reader = fs::File::open(csv)
+        .map(io::BufReader::new)
+        .map_err(|err| format!("can’t read {}: {}", csv.display(), err))?;
+    let count = sheet
+        .import_csv(reader)
+        .map_err(|err| format!("{}: {}", csv.display(), err))?;
+    sheet.back_up(config.backups).ok();
+    sheet
+        .save()
+        .map_err(|err| format!("can’t save {}: {}", sheet.path.display(), err))?;
+    println!("Imported {} entries into {}", count, sheet.path.display());
+    Ok(())
+}
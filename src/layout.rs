@@ -1,23 +1,89 @@
+use crate::config::{Columns, Orientation, Theme};
 use ratatui::layout::*;
-use ratatui::style::{Color, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::Line;
 use ratatui::widgets::*;
-pub fn selectable_list<'a, C: AsRef<str>>(title: Line<'a>, content: &'a [C]) -> List<'a> {
+/**
+ * Rows flagged as muted are drawn in the theme's pause color, the `warned` one in its
+ * warning color, the `marked` ones underlined and the `italic` ones in italics.
+ */
+pub fn selectable_list<'a, C: AsRef<str>>(
+    title: Line<'a>,
+    content: &'a [(C, bool)],
+    marked: &[usize],
+    italic: &[usize],
+    warned: Option<usize>,
+    theme: &'a Theme,
+) -> List<'a> {
     let items = content
         .iter()
-        .map(|item| ListItem::new(item.as_ref()))
+        .enumerate()
+        .map(|(index, (item, muted))| {
+            let mut style = Style::default();
+            if *muted {
+                style = style.fg(theme.pause);
+            }
+            if warned == Some(index) {
+                style = style.fg(theme.warning);
+            }
+            if marked.contains(&index) {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            if italic.contains(&index) {
+                style = style.add_modifier(Modifier::ITALIC);
+            }
+            ListItem::new(item.as_ref()).style(style)
+        })
         .collect::<Vec<_>>();
 
     List::new(items)
         .block(Block::default().title(title).borders(Borders::ALL))
-        .highlight_style(Style::default().fg(Color::LightGreen))
-        .highlight_symbol(Line::from(">"))
+        .style(Style::default().fg(theme.foreground).bg(theme.background))
+        .highlight_style(
+            Style::default()
+                .fg(theme.highlight_foreground)
+                .bg(theme.highlight_background),
+        )
+        .highlight_symbol(Line::from(theme.highlight_symbol.as_str()))
+        // Keep a neighbouring row in view when scrolling to either end.
+        .scroll_padding(1)
 }
 
-pub fn layout(r: Rect) -> Vec<Rect> {
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+/// Terminals narrower than this stack the summary below the list in `Orientation::Auto`.
+const AUTO_VERTICAL_BELOW: u16 = 80;
+
+/// Always returns the list area, the summary area, and the todo area, in that order.
+/// The todos share the summary's slot, split across the other direction.
+pub fn layout(r: Rect, columns: Columns, orientation: Orientation) -> Vec<Rect> {
+    let direction = match orientation {
+        Orientation::Horizontal => Direction::Horizontal,
+        Orientation::Vertical => Direction::Vertical,
+        Orientation::Auto if r.width < AUTO_VERTICAL_BELOW => Direction::Vertical,
+        Orientation::Auto => Direction::Horizontal,
+    };
+    let constraints = match columns {
+        Columns::Percent { list, summary } => [
+            Constraint::Percentage(list),
+            Constraint::Percentage(summary),
+        ],
+        Columns::Length { list, summary } => {
+            [Constraint::Length(list), Constraint::Length(summary)]
+        }
+    };
+    let mut chunks = Layout::default()
+        .direction(direction)
+        .constraints(constraints.as_ref())
         .split(r)
-        .to_vec()
+        .to_vec();
+    let side_direction = match direction {
+        Direction::Horizontal => Direction::Vertical,
+        Direction::Vertical => Direction::Horizontal,
+    };
+    let side = Layout::default()
+        .direction(side_direction)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+        .split(chunks[1]);
+    chunks[1] = side[0];
+    chunks.push(side[1]);
+    chunks
 }
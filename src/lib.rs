@@ -0,0 +1,9 @@
+#![forbid(unsafe_code)]
+//! The data model and computations behind tracc, usable without the TUI:
+//! loading and saving timesheets, summing them up, and the export formats.
+pub mod config;
+pub mod error;
+pub mod timesheet;
+pub mod todos;
+#[macro_use]
+extern crate lazy_static;
@@ -9,53 +9,117 @@ use crossterm::{
 };
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
-use std::io;
+use std::{
+    io, panic, process,
+    sync::atomic::{AtomicBool, Ordering},
+};
+// The binary's own `tracc` module is the app, so the library is reached through `::tracc`.
+use ::tracc::{config, timesheet, todos};
+mod cli;
+mod clipboard;
 mod confirm;
 mod help;
+mod import;
 mod layout;
-mod timesheet;
+mod report;
 mod tracc;
-use tracc::Tracc;
-#[macro_use]
-extern crate lazy_static;
+use crate::tracc::Tracc;
 
 struct TerminalSession;
 
+/// Whether the terminal is in raw mode on the alternate screen and needs restoring.
+static TERMINAL_ACTIVE: AtomicBool = AtomicBool::new(false);
+
 impl TerminalSession {
+    /// A panic restores the terminal before its message is printed, so the message stays visible.
     fn enter() -> Result<Self, io::Error> {
         enable_raw_mode()?;
+        TERMINAL_ACTIVE.store(true, Ordering::SeqCst);
         if let Err(err) = execute!(
             io::stdout(),
             EnterAlternateScreen,
             PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES),
             EnableMouseCapture
         ) {
-            let _ = disable_raw_mode();
+            restore_terminal();
             return Err(err);
         }
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            default_hook(info);
+        }));
         Ok(Self)
     }
 }
 
 impl Drop for TerminalSession {
     fn drop(&mut self) {
-        let _ = execute!(
-            io::stdout(),
-            PopKeyboardEnhancementFlags,
-            DisableMouseCapture
-        );
-        let _ = disable_raw_mode();
-        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+        restore_terminal();
+    }
+}
+
+/// Only the first call after `enter` does anything, so the panic hook and `Drop` can both call it.
+fn restore_terminal() {
+    if !TERMINAL_ACTIVE.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let _ = execute!(
+        io::stdout(),
+        PopKeyboardEnhancementFlags,
+        DisableMouseCapture
+    );
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen);
+}
+
+fn main() {
+    let options = cli::parse().unwrap_or_else(|err| {
+        eprintln!("tracc: {}", err);
+        process::exit(1);
+    });
+    let config = config::Config::load().unwrap_or_else(|err| {
+        eprintln!("tracc: {}", err);
+        process::exit(1);
+    });
+    if let Some(warning) = timesheet::set_timezone(config.timezone) {
+        eprintln!("tracc: warning: {}", warning);
+    }
+    let result = match options.mode {
+        cli::Mode::Report => report::print(options, config),
+        cli::Mode::Week => report::print_week(config),
+        cli::Mode::Check => report::check(options.file.as_deref(), config),
+        cli::Mode::Status => report::print_status(options.file.as_deref(), config),
+        cli::Mode::Import(ref csv) => import::run(csv, options.file.as_deref(), config),
+        cli::Mode::ExportIcs(ref out) => report::export_ics(out, options.file.as_deref(), config),
+        cli::Mode::Interactive => match TerminalSession::enter() {
+            Ok(session) => run(session, options, config).map_err(|err| err.to_string()),
+            Err(err) => {
+                eprintln!(
+                    "tracc: can’t set up the terminal ({}); printing the report instead",
+                    err
+                );
+                report::print(options, config)
+            }
+        },
+    };
+    if let Err(err) = result {
+        eprintln!("tracc: {}", err);
+        process::exit(1);
     }
 }
 
-fn main() -> Result<(), io::Error> {
-    let _session = TerminalSession::enter()?;
+/// Runs the TUI. The terminal is restored before any error is returned.
+fn run(
+    _session: TerminalSession,
+    options: cli::Options,
+    config: config::Config,
+) -> Result<(), io::Error> {
     let stdout = io::stdout();
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
     terminal.hide_cursor()?;
     terminal.clear()?;
-    let mut tracc = Tracc::new(terminal);
+    let mut tracc = Tracc::new(terminal, config, options.file)?;
     tracc.run()
 }
@@ -0,0 +1,129 @@
+use crate::cli::{self, Format, Options};
+use crate::config::Config;
+use crate::timesheet::{self, TimeSheet};
+use std::{fs, io, path::Path, rc::Rc};
+use time::Duration;
+
+/// Prints the summary of a sheet as plain text, without starting the TUI.
+/// Without `file`, today's sheet is used, and a missing file counts as an empty day.
+pub fn print(options: Options, config: Config) -> Result<(), String> {
+    let Options {
+        file,
+        format,
+        billable,
+        ..
+    } = options;
+    let config = Rc::new(config);
+    let sheet = open(file.as_deref(), &config)?;
+    if sheet.reordered_on_load {
+        eprintln!(
+            "tracc: warning: {} had entries out of order; they were sorted by time",
+            sheet.path.display()
+        );
+    }
+
+    match format {
+        Format::Json => {
+            println!("{}", sheet.summary_json());
+            return Ok(());
+        }
+        Format::Csv => {
+            print!("{}", sheet.to_csv());
+            return Ok(());
+        }
+        Format::JsonLines => {
+            print!("{}", sheet.to_jsonl());
+            return Ok(());
+        }
+        Format::Markdown => {
+            print!("{}", sheet.to_markdown());
+            return Ok(());
+        }
+        Format::Text => {}
+    }
+
+    if billable {
+        let tasks = sheet.billable_time_by_tasks();
+        if !tasks.is_empty() {
+            println!("{}", tasks);
+        }
+        println!("Sum: {}", sheet.billable_sum_as_str());
+        return Ok(());
+    }
+
+    println!("{}", sheet.plain_summary());
+    if let Some(billable) = sheet.billable_total_str() {
+        println!("Billable: {}", billable);
+    }
+    println!("{}", sheet.pause_time());
+    for section in [sheet.time_by_tags(), sheet.time_by_project()] {
+        if !section.is_empty() {
+            println!("\n{}", section);
+        }
+    }
+    Ok(())
+}
+
+/// Reads `-` from stdin and loads any other `file`, where a missing one is an error.
+/// Without `file`, today's sheet is opened, and a missing file counts as an empty day.
+fn open(file: Option<&Path>, config: &Rc<Config>) -> Result<TimeSheet, String> {
+    match file {
+        Some(path) if cli::reads_stdin(Some(path)) => {
+            TimeSheet::read(io::stdin().lock(), path.to_path_buf(), None, config)
+        }
+        Some(path) => TimeSheet::load(path.to_path_buf(), None, config),
+        None => TimeSheet::open(TimeSheet::current_date(), config),
+    }
+    .map_err(|err| err.to_string())
+}
+
+/// Prints today's sheet, or `file`, as the one line of `TimeSheet::status_line`.
+pub fn print_status(file: Option<&Path>, config: Config) -> Result<(), String> {
+    let config = Rc::new(config);
+    println!("{}", open(file, &config)?.status_line());
+    Ok(())
+}
+
+/// Prints the tasks of today and the six days before it, summed up over all of them.
+/// Days without a sheet count as empty.
+pub fn print_week(config: Config) -> Result<(), String> {
+    let config = Rc::new(config);
+    let today = TimeSheet::current_date();
+    let first = today - Duration::days(6);
+    let sheets = (0..7)
+        .map(|offset| TimeSheet::open(first + Duration::days(offset), &config))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+    println!("{} to {}", first, today);
+    println!("{}", timesheet::combined_summary(&sheets, &config));
+    Ok(())
+}
+
+/// Prints the sheet as saving it would write it, after the same sorting loading does.
+/// Unlike `print`, a missing file is an error, since there is nothing to check.
+pub fn check(file: Option<&Path>, config: Config) -> Result<(), String> {
+    let config = Rc::new(config);
+    let date = TimeSheet::current_date();
+    let sheet = match file {
+        Some(path) => open(Some(path), &config)?,
+        None => TimeSheet::load(
+            timesheet::storage_path_for(date, &config, None),
+            Some(date),
+            &config,
+        )
+        .map_err(|err| err.to_string())?,
+    };
+    for warning in sheet.warnings() {
+        eprintln!("tracc: warning: {}", warning);
+    }
+    let json = sheet.to_saved_json().map_err(|err| err.to_string())?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Writes today’s sheet, or `file`, to `out` as an iCalendar file.
+pub fn export_ics(out: &Path, file: Option<&Path>, config: Config) -> Result<(), String> {
+    let config = Rc::new(config);
+    let sheet = open(file, &config)?;
+    fs::write(out, sheet.to_ics()).map_err(|err| format!("can’t write {}: {}", out.display(), err))
+}
@@ -1,3 +1,5 @@
+use crate::config::{Config, Timezone};
+use crate::error::TraccError;
 use itertools::Itertools;
 use serde::ser::SerializeTuple;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
@@ -5,34 +7,127 @@ use serde_json::from_reader;
 use std::{
     collections,
     convert::TryFrom,
-    default, env, fmt, fs, io,
+    default, env, fmt, fs,
+    io::{self, Write},
     path::{Path, PathBuf},
+    rc::Rc,
+    sync::OnceLock,
+};
+use time::{
+    format_description::well_known::Rfc3339, macros::format_description, Date, Duration, Month,
+    OffsetDateTime, PrimitiveDateTime, Time, UtcOffset,
 };
-use time::{Date, Duration, OffsetDateTime};
 
 #[derive(Clone)]
 pub struct TimeSheet {
     pub date: Date,
+    pub path: PathBuf,
     pub times: Vec<TimePoint>,
     pub selected: usize,
     pub register: Option<TimePoint>,
+    pub config: Rc<Config>,
+    /// Whether the file had entries out of order, which loading sorted, until it is saved.
+    pub reordered_on_load: bool,
 }
 
-const MAIN_PAUSE_TEXT: &str = "pause";
-const PAUSE_TEXTS: [&str; 4] = [MAIN_PAUSE_TEXT, "lunch", "break", "end"];
 const END_TEXT: &str = "end";
+const BILLING_STEP_MINUTES: i64 = 15;
 lazy_static! {
-    static ref OVERRIDE_REGEX: regex::Regex = regex::Regex::new("\\[(.*)\\]").unwrap();
+    static ref TAG_REGEX: regex::Regex = regex::Regex::new("(?:^|\\s)#([\\w-]+)").unwrap();
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TimePoint {
     text: String,
-    #[serde(
-        serialize_with = "serialize_minutes",
-        deserialize_with = "deserialize_minutes"
-    )]
+    /// Minutes since midnight of the sheet's date.
+    /// Entries past midnight keep counting up (e.g. 24:10), so windows never go negative.
     time: i64,
+    /// Seconds past `time`; only ever set with the `seconds` setting.
+    seconds: i64,
+    /// Free-form detail shown in the list; grouping only ever looks at `text`.
+    note: Option<String>,
+    /// Whether the work counts towards `billable_total`; it counts for the task totals either way.
+    pub billable: bool,
+    /// A client or project code for invoicing, summed up by `time_by_project`.
+    pub project: Option<String>,
+}
+
+/// Appended to the list row that contains the current moment.
+const NOW_MARKER: &str = "  ◂ now";
+
+/// The bucket of `time_by_project` for entries without a project.
+const UNASSIGNED_PROJECT: &str = "(unassigned)";
+
+/// Separates the note from the task while typing, as in "bugfix // the null deref".
+pub const NOTE_DELIMITER: &str = " // ";
+
+/**
+ * The on-disk form of a `TimePoint`. Its time is written as an RFC 3339 timestamp,
+ * so a sheet can be read without knowing which day it belongs to.
+ */
+#[derive(Serialize, Deserialize)]
+struct StoredTimePoint {
+    text: String,
+    time: StoredTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    /// Only written for non-billable entries, so older files count as billable.
+    #[serde(default = "billable_default", skip_serializing_if = "is_billable")]
+    billable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+}
+
+fn billable_default() -> bool {
+    true
+}
+
+fn is_billable(billable: &bool) -> bool {
+    *billable
+}
+
+enum StoredTime {
+    /// Minutes and seconds since the sheet's midnight, as files written before timestamps have it.
+    Clock(i64, i64),
+    DateTime(OffsetDateTime),
+}
+
+impl TimePoint {
+    /// Only the wall-clock time of a timestamp counts; its offset is what the zone was when it was written.
+    fn from_stored(stored: StoredTimePoint, date: Date) -> Self {
+        let (time, seconds) = match stored.time {
+            StoredTime::Clock(time, seconds) => (time, seconds),
+            StoredTime::DateTime(at) => (
+                (at.date() - date).whole_days() * 24 * 60
+                    + i64::from(at.hour()) * 60
+                    + i64::from(at.minute()),
+                i64::from(at.second()),
+            ),
+        };
+        TimePoint {
+            note: stored.note,
+            billable: stored.billable,
+            project: stored.project,
+            ..TimePoint::new(&stored.text, time).with_seconds(seconds)
+        }
+    }
+
+    fn to_stored(&self, date: Date) -> StoredTimePoint {
+        StoredTimePoint {
+            text: self.text.clone(),
+            time: StoredTime::DateTime(self.datetime_on(date)),
+            note: self.note.clone(),
+            billable: self.billable,
+            project: self.project.clone(),
+        }
+    }
+
+    /// When this entry is on a sheet for `date`, in the configured zone.
+    fn datetime_on(&self, date: Date) -> OffsetDateTime {
+        let at =
+            PrimitiveDateTime::new(date, Time::MIDNIGHT) + Duration::seconds(self.total_seconds());
+        at.assume_offset(offset_at(at))
+    }
 }
 
 impl TimePoint {
@@ -40,81 +135,142 @@ impl TimePoint {
         Self {
             text: String::from(text),
             time,
+            seconds: 0,
+            note: None,
+            billable: true,
+            project: None,
+        }
+    }
+
+    /// An entry from typed input; anything after `NOTE_DELIMITER` becomes its note.
+    pub fn from_input(input: &str, time: i64) -> Self {
+        let (text, note) = split_note(input);
+        Self {
+            note,
+            ..Self::new(text, time)
+        }
+    }
+
+    /// The text and note in the form `from_input` reads them.
+    pub fn input_text(&self) -> String {
+        match &self.note {
+            Some(note) => format!("{}{}{}", self.text, NOTE_DELIMITER, note),
+            None => self.text.clone(),
         }
     }
 
+    pub fn with_seconds(mut self, seconds: i64) -> Self {
+        self.seconds = seconds.clamp(0, 59);
+        self
+    }
+
     pub fn time(&self) -> i64 {
         self.time
     }
+
+    pub fn seconds(&self) -> i64 {
+        self.seconds
+    }
+
+    fn total_seconds(&self) -> i64 {
+        self.time * 60 + self.seconds
+    }
 }
 
-fn serialize_minutes<S>(minutes: &i64, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    let mut tuple = serializer.serialize_tuple(4)?;
-    tuple.serialize_element(&minutes.div_euclid(60))?;
-    tuple.serialize_element(&minutes.rem_euclid(60))?;
-    tuple.serialize_element(&0)?;
-    tuple.serialize_element(&0)?;
-    tuple.end()
+impl Serialize for StoredTime {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            StoredTime::Clock(minutes, seconds) => {
+                let mut tuple = serializer.serialize_tuple(4)?;
+                tuple.serialize_element(&minutes.div_euclid(60))?;
+                tuple.serialize_element(&minutes.rem_euclid(60))?;
+                tuple.serialize_element(seconds)?;
+                tuple.serialize_element(&0)?;
+                tuple.end()
+            }
+            StoredTime::DateTime(at) => {
+                serializer.serialize_str(&at.format(&Rfc3339).map_err(serde::ser::Error::custom)?)
+            }
+        }
+    }
 }
 
-fn deserialize_minutes<'de, D>(deserializer: D) -> Result<i64, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    struct MinutesVisitor;
+/**
+ * An RFC 3339 timestamp (or an ISO 8601 one without offset),
+ * and for older files an integer minute offset, an `HH:MM` string,
+ * or an `[hours, minutes, seconds, 0]` tuple.
+ */
+impl<'de> Deserialize<'de> for StoredTime {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(StoredTimeVisitor)
+    }
+}
 
-    impl<'de> de::Visitor<'de> for MinutesVisitor {
-        type Value = i64;
+struct StoredTimeVisitor;
 
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("an integer minute offset or a time string")
-        }
+impl<'de> de::Visitor<'de> for StoredTimeVisitor {
+    type Value = StoredTime;
 
-        fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
-            Ok(value)
-        }
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a timestamp, an integer minute offset or a time string")
+    }
 
-        fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            i64::try_from(value).map_err(|_| E::custom("time value is too large"))
-        }
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(StoredTime::Clock(value, 0))
+    }
 
-        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            parse_minutes(value).map_err(E::custom)
-        }
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        i64::try_from(value)
+            .map(|value| StoredTime::Clock(value, 0))
+            .map_err(|_| E::custom("time value is too large"))
+    }
 
-        fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            self.visit_str(&value)
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if let Ok(at) = OffsetDateTime::parse(value, &Rfc3339) {
+            return Ok(StoredTime::DateTime(at));
         }
-
-        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-        where
-            A: de::SeqAccess<'de>,
-        {
-            let hour: i64 = seq
-                .next_element()?
-                .ok_or_else(|| de::Error::invalid_length(0, &self))?;
-            let minute: i64 = seq
-                .next_element()?
-                .ok_or_else(|| de::Error::invalid_length(1, &self))?;
-            let _ = seq.next_element::<de::IgnoredAny>()?;
-            let _ = seq.next_element::<de::IgnoredAny>()?;
-            Ok(hour * 60 + minute)
+        let local = format_description!("[year]-[month]-[day]T[hour]:[minute]:[second]");
+        if let Ok(at) = PrimitiveDateTime::parse(value, local) {
+            return Ok(StoredTime::DateTime(at.assume_utc()));
         }
+        parse_minutes(value)
+            .map(|value| StoredTime::Clock(value, 0))
+            .map_err(E::custom)
     }
 
-    deserializer.deserialize_any(MinutesVisitor)
+    fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_str(&value)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let hour: i64 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let minute: i64 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let second: i64 = seq.next_element()?.unwrap_or(0);
+        let _ = seq.next_element::<de::IgnoredAny>()?;
+        Ok(StoredTime::Clock(hour * 60 + minute, second))
+    }
 }
 
 pub fn parse_minutes(value: &str) -> Result<i64, String> {
@@ -133,6 +289,60 @@ pub fn parse_minutes(value: &str) -> Result<i64, String> {
         .map_err(|_| format!("invalid time value: {value}"))
 }
 
+/// `parse_minutes` with optional seconds, as in `HH:MM:SS`.
+fn parse_clock(value: &str) -> Result<(i64, i64), String> {
+    let value = value.trim();
+    if value.matches(':').count() < 2 {
+        return Ok((parse_minutes(value)?, 0));
+    }
+    let (clock, seconds) = value.rsplit_once(':').unwrap();
+    let seconds = seconds
+        .parse::<i64>()
+        .ok()
+        .filter(|seconds| (0..60).contains(seconds))
+        .ok_or_else(|| format!("invalid second in time value: {value}"))?;
+    Ok((parse_minutes(clock)?, seconds))
+}
+
+/**
+ * Like `parse_minutes`, but also relative: `now`, `now-15` or `now+5` count from `now`,
+ * and `+20` or `-10` from `base`, the neighbouring entry. Offsets are whole minutes.
+ */
+pub fn parse_time_input(value: &str, now: i64, base: Option<i64>) -> Result<i64, String> {
+    let value = value.trim();
+    let (origin, offset) = if let Some(offset) = value.strip_prefix("now") {
+        (now, offset)
+    } else if value.starts_with(['+', '-']) {
+        let base = base.ok_or_else(|| format!("no entry for {} to count from", value))?;
+        (base, value)
+    } else {
+        return parse_minutes(value);
+    };
+    let offset = match offset.trim() {
+        "" => 0,
+        offset => {
+            let mut chars = offset.chars();
+            let sign = chars.next();
+            let minutes = chars.as_str().trim();
+            let minutes = minutes
+                .parse::<i64>()
+                .ok()
+                .filter(|_| minutes.chars().all(|chr| chr.is_ascii_digit()))
+                .ok_or_else(|| format!("invalid time value: {value}"))?;
+            match sign {
+                Some('+') => minutes,
+                Some('-') => -minutes,
+                _ => return Err(format!("invalid time value: {value}")),
+            }
+        }
+    };
+    let time = origin + offset;
+    if !(0..48 * 60).contains(&time) {
+        return Err(format!("time value out of range: {value}"));
+    }
+    Ok(time)
+}
+
 fn clock_time_to_minutes(value: &str, hours: &str, minutes: &str) -> Result<i64, String> {
     let hours = hours
         .parse::<i64>()
@@ -152,48 +362,65 @@ fn clock_time_to_minutes(value: &str, hours: &str, minutes: &str) -> Result<i64,
     Ok(hours * 60 + minutes)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::parse_minutes;
-
-    #[test]
-    fn rejects_minutes_over_59_in_clock_form() {
-        assert!(parse_minutes("10:200").is_err());
-    }
-
-    #[test]
-    fn accepts_clock_form_with_valid_minutes() {
-        assert_eq!(parse_minutes("10:20").unwrap(), 620);
-    }
+static TIMEZONE: OnceLock<Timezone> = OnceLock::new();
 
-    #[test]
-    fn rejects_compact_form_with_hour_at_48_or_more() {
-        assert!(parse_minutes("4820").is_err());
-    }
+/**
+ * Sets the zone `now` is taken in. Called once at startup, before any sheet is opened
+ * and while there is only one thread, which some platforms need to read the local offset.
+ * If the local offset can’t be determined (e.g. no `/etc/localtime` in a container),
+ * UTC is used instead and a warning is returned.
+ */
+pub fn set_timezone(timezone: Timezone) -> Option<String> {
+    let (timezone, warning) = match timezone {
+        Timezone::Local if UtcOffset::current_local_offset().is_err() => (
+            Timezone::Fixed(UtcOffset::UTC),
+            Some(
+                "can’t determine the local time zone, using UTC; set \"timezone\" in the config"
+                    .to_string(),
+            ),
+        ),
+        timezone => (timezone, None),
+    };
+    TIMEZONE.set(timezone).ok();
+    warning
+}
 
-    #[test]
-    fn rejects_compact_form_with_minutes_over_59() {
-        assert!(parse_minutes("1060").is_err());
+/// The offset of the configured zone at a wall-clock time, for the timestamps in a sheet.
+fn offset_at(at: PrimitiveDateTime) -> UtcOffset {
+    match TIMEZONE.get().copied().unwrap_or_default() {
+        Timezone::Local => UtcOffset::local_offset_at(at.assume_utc()).unwrap_or(UtcOffset::UTC),
+        Timezone::Fixed(offset) => offset,
     }
+}
 
-    #[test]
-    fn accepts_three_digit_compact_form() {
-        assert_eq!(parse_minutes("940").unwrap(), 580);
+/// The only source of "now", so every entry and summary agrees on the zone.
+fn now() -> OffsetDateTime {
+    match TIMEZONE.get().copied().unwrap_or_default() {
+        Timezone::Local => {
+            OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc())
+        }
+        Timezone::Fixed(offset) => OffsetDateTime::now_utc().to_offset(offset),
     }
 }
 
 fn current_minutes_since(date: Date) -> i64 {
-    let now = OffsetDateTime::now_local().unwrap();
+    current_clock_since(date).0
+}
+
+/// Minutes since midnight of `date` and the seconds into the current minute.
+fn current_clock_since(date: Date) -> (i64, i64) {
+    let now = now();
     let day_diff = (now.date() - date).whole_days();
-    day_diff * 24 * 60 + now.time().hour() as i64 * 60 + now.time().minute() as i64
+    let minutes = day_diff * 24 * 60 + now.time().hour() as i64 * 60 + now.time().minute() as i64;
+    (minutes, now.time().second() as i64)
 }
 
 fn today() -> Date {
-    OffsetDateTime::now_local().unwrap().date()
+    now().date()
 }
 
 #[cfg(windows)]
-fn data_dir() -> PathBuf {
+pub(crate) fn data_dir() -> PathBuf {
     env::var_os("LOCALAPPDATA")
         .map(PathBuf::from)
         .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
@@ -201,7 +428,7 @@ fn data_dir() -> PathBuf {
 }
 
 #[cfg(target_os = "macos")]
-fn data_dir() -> PathBuf {
+pub(crate) fn data_dir() -> PathBuf {
     env::var_os("HOME")
         .map(|home| {
             PathBuf::from(home)
@@ -212,7 +439,7 @@ fn data_dir() -> PathBuf {
 }
 
 #[cfg(all(not(windows), not(target_os = "macos")))]
-fn data_dir() -> PathBuf {
+pub(crate) fn data_dir() -> PathBuf {
     env::var_os("XDG_DATA_HOME")
         .map(PathBuf::from)
         .or_else(|| {
@@ -221,19 +448,108 @@ fn data_dir() -> PathBuf {
         .unwrap_or_else(|| PathBuf::from("."))
 }
 
-pub fn storage_path_for(date: Date) -> PathBuf {
+/// Named sheets from the `sheets` setting get their own subdirectory with the same layout.
+pub fn storage_path_for(date: Date, config: &Config, sheet: Option<&str>) -> PathBuf {
     let (year, month, day) = date.to_calendar_date();
-    data_dir()
-        .join("tracc")
-        .join("timesheets")
+    let base = timesheet_base(config);
+    sheet
+        .map_or(base.clone(), |sheet| base.join(sheet))
         .join(format!("{}", year))
         .join(format!("{:02}", u8::from(month)))
         .join(format!("{:02}.json", day))
 }
 
+/// The directory with the dated sheets, `timesheet_dir` or `tracc/timesheets` in the data directory.
+pub(crate) fn timesheet_base(config: &Config) -> PathBuf {
+    config
+        .timesheet_dir
+        .clone()
+        .unwrap_or_else(|| data_dir().join("tracc").join("timesheets"))
+}
+
+/// The selection each sheet was left with, by sheet path, kept out of the sheets themselves.
+fn selections_path(config: &Config) -> PathBuf {
+    timesheet_base(config).join("selection.json")
+}
+
+/// A missing or unreadable file just means nothing is remembered.
+fn read_selections(config: &Config) -> collections::BTreeMap<PathBuf, usize> {
+    fs::File::open(selections_path(config))
+        .ok()
+        .and_then(|file| from_reader(io::BufReader::new(file)).ok())
+        .unwrap_or_default()
+}
+
+/// `log` with `lines` from `daily_log_line` in it: each replaces the line of its date or is appended.
+pub fn merge_daily_log(log: &str, lines: &[String]) -> String {
+    let date = |line: &str| line.split('\t').next().unwrap_or_default().to_string();
+    let mut pending: Vec<&String> = lines.iter().collect();
+    let mut merged = String::new();
+    for line in log.lines() {
+        match pending.iter().position(|new| date(new) == date(line)) {
+            Some(index) => merged.push_str(pending.remove(index)),
+            None if lines.iter().any(|new| date(new) == date(line)) => continue,
+            None => merged.push_str(line),
+        }
+        merged.push('\n');
+    }
+    for line in pending {
+        merged.push_str(line);
+        merged.push('\n');
+    }
+    merged
+}
+
+/// Where `daily_log` lines go: `daily_log_path`, or `daily.log` next to the sheets.
+pub fn daily_log_path(config: &Config) -> PathBuf {
+    config
+        .daily_log_path
+        .clone()
+        .unwrap_or_else(|| timesheet_base(config).join("daily.log"))
+}
+
+/// Whether typed input has no task, only blanks or a note.
+pub fn is_empty_input(input: &str) -> bool {
+    split_note(input).0.trim().is_empty()
+}
+
+/// The task and the trimmed note of typed input; an empty note is none.
+/// A note without a task is empty input, see `is_empty_input`.
+fn split_note(input: &str) -> (&str, Option<String>) {
+    match input.split_once(NOTE_DELIMITER) {
+        Some((text, _)) if text.trim().is_empty() => ("", None),
+        Some((text, note)) if !note.trim().is_empty() => (text, Some(note.trim().to_string())),
+        Some((text, _)) => (text, None),
+        None => (input, None),
+    }
+}
+
 impl fmt::Display for TimePoint {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "[{}] {}", format_minutes(self.time), self.text)
+        write!(f, "[{}] {}", format_minutes(self.time), self.text)?;
+        if let Some(project) = &self.project {
+            write!(f, " @{}", project)?;
+        }
+        if let Some(note) = &self.note {
+            write!(f, " — {}", note)?;
+        }
+        Ok(())
+    }
+}
+
+impl TimePoint {
+    /// Like `Display`, with the time rendered by `format_time_of_day`.
+    pub fn format_with(&self, format: &str) -> String {
+        let time = format_clock(self.time, self.seconds, format)
+            .unwrap_or_else(|_| format_minutes(self.time));
+        let mut row = format!("[{}] {}", time, self.text);
+        if let Some(project) = &self.project {
+            row += &format!(" @{}", project);
+        }
+        if let Some(note) = &self.note {
+            row += &format!(" — {}", note);
+        }
+        row
     }
 }
 
@@ -243,43 +559,342 @@ impl default::Default for TimePoint {
     }
 }
 
-fn read_times(path: &Path) -> Option<Vec<TimePoint>> {
-    fs::File::open(path)
-        .ok()
-        .map(io::BufReader::new)
-        .and_then(|r| from_reader(r).ok())
+/// `.DD.json.tmp` in the same directory, so the rename stays on one filesystem.
+fn temp_path_for(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.tmp", name))
+}
+
+/// Writes to a temp file first, so a crash never leaves `path` half-written.
+pub(crate) fn write_json_atomically<T: Serialize + ?Sized>(
+    path: &Path,
+    value: &T,
+) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = temp_path_for(path);
+    let mut writer = io::BufWriter::new(fs::File::create(&tmp_path)?);
+    serde_json::to_writer_pretty(&mut writer, value)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    fs::rename(&tmp_path, path)
+}
+
+/// Files from before timestamps store times relative to `date`, the sheet's day, and load just the same.
+/// `path` only names the source in errors; the entries come from `reader`.
+/// Without a `date`, the sheet's day comes from the file; see `file_date`.
+fn read_times(
+    reader: impl io::Read,
+    path: &Path,
+    date: Option<Date>,
+) -> Result<(Date, Vec<TimePoint>), TraccError> {
+    let stored: Vec<StoredTimePoint> = from_reader(io::BufReader::new(reader))
+        .map_err(|err| TraccError::json(path.to_path_buf(), err))?;
+    let date = date.unwrap_or_else(|| file_date(path, &stored));
+    let times = stored
+        .into_iter()
+        .map(|stored| TimePoint::from_stored(stored, date))
+        .collect();
+    Ok((date, times))
+}
+
+/**
+ * The day a file given by the user belongs to: that of its earliest timestamp,
+ * else the one of a `YYYY/MM/DD.json` path like the dated sheets have, else today.
+ * Anchoring it to today instead would move every entry on the next save.
+ */
+fn file_date(path: &Path, stored: &[StoredTimePoint]) -> Date {
+    stored
+        .iter()
+        .filter_map(|stored| match stored.time {
+            StoredTime::DateTime(at) => Some(at.date()),
+            StoredTime::Clock(..) => None,
+        })
+        .min()
+        .or_else(|| date_from_path(path))
+        .unwrap_or_else(today)
+}
+
+fn date_from_path(path: &Path) -> Option<Date> {
+    if path.extension()? != "json" {
+        return None;
+    }
+    let day = path.file_stem()?.to_str()?.parse::<u8>().ok()?;
+    let month_dir = path.parent()?;
+    let month = month_dir.file_name()?.to_str()?.parse::<u8>().ok()?;
+    let year = month_dir
+        .parent()?
+        .file_name()?
+        .to_str()?
+        .parse::<i32>()
+        .ok()?;
+    Date::from_calendar_date(year, Month::try_from(month).ok()?, day).ok()
 }
 
 /**
  * If a time text contains "[something]",
- * only use the message inside the brackets.
+ * only use the message inside the brackets; the rest is a note that only the list shows.
+ * Several groups are joined, so "[acme] call about [billing]" counts as "acme / billing".
+ * `#tags` are not part of the effective text unless there is nothing else.
  */
-fn effective_text(s: String) -> String {
-    let text = OVERRIDE_REGEX
-        .captures(&s)
-        // index 0 is the entire string
-        .and_then(|caps| caps.get(1))
-        .map(|m| m.as_str())
-        .unwrap_or(&s);
-    if PAUSE_TEXTS.contains(&text) {
-        MAIN_PAUSE_TEXT
+fn effective_text(s: String, config: &Config) -> String {
+    let text = ungrouped_text(s);
+    if !is_comment(&text) && config.is_pause(&text) {
+        config.pause_label.clone()
     } else {
         text
     }
-    .to_string()
+}
+
+/// `effective_text` before the pauses are collapsed into `pause_label`.
+fn ungrouped_text(s: String) -> String {
+    if is_comment(&s) {
+        return s;
+    }
+    let untagged = TAG_REGEX.replace_all(&s, "");
+    let s = match untagged.trim() {
+        "" => s.as_str(),
+        untagged => untagged,
+    };
+    override_groups(s)
+        .map(|groups| groups.join(" / "))
+        .unwrap_or_else(|| s.to_string())
+}
+
+/**
+ * How well `text` matches `term`, both lowercase, or `None` if it doesn't contain its letters in order.
+ * Any substring match outranks every scattered one; among those, earlier and word-start hits win,
+ * and scattered matches lose a point per skipped character. A blank term matches nothing.
+ */
+fn fuzzy_score(text: &str, term: &str) -> Option<(bool, i64)> {
+    if term.trim().is_empty() {
+        return None;
+    }
+    let at_word_start = |index: usize| {
+        text[..index]
+            .chars()
+            .next_back()
+            .is_none_or(|before| !before.is_alphanumeric())
+    };
+    if let Some(index) = text.find(term) {
+        let bonus = if at_word_start(index) { 100 } else { 0 };
+        let position = text[..index].chars().count() as i64;
+        return Some((true, bonus - position));
+    }
+    let mut score = 0;
+    let mut last = None;
+    // Positions count characters, so accents don't widen the gaps.
+    let mut chars = text.char_indices().enumerate();
+    for wanted in term.chars() {
+        let (position, (index, _)) = chars.find(|(_, (_, chr))| *chr == wanted)?;
+        if at_word_start(index) {
+            score += 10;
+        }
+        score -= last.map_or(0, |last| (position - last) as i64);
+        last = Some(position + 1);
+    }
+    Some((false, score))
+}
+
+/// Texts starting with `--` or `//`, like `--- afternoon ---`, only structure the list.
+fn is_comment(text: &str) -> bool {
+    let text = text.trim_start();
+    text.starts_with("--") || text.starts_with("//")
+}
+
+/**
+ * The trimmed, non-empty `[groups]` of a text. Nested or unbalanced brackets
+ * make the text ambiguous, so they count as having no groups at all.
+ */
+fn override_groups(s: &str) -> Option<Vec<&str>> {
+    let mut groups = Vec::new();
+    let mut start = None;
+    for (index, chr) in s.char_indices() {
+        match (chr, start) {
+            ('[', None) => start = Some(index + 1),
+            (']', Some(from)) => {
+                let group = s[from..index].trim();
+                if !group.is_empty() {
+                    groups.push(group);
+                }
+                start = None;
+            }
+            ('[', Some(_)) | (']', None) => return None,
+            _ => {}
+        }
+    }
+    (start.is_none() && !groups.is_empty()).then_some(groups)
+}
+
+/// All `#word` tokens of a text, without the `#`.
+fn tags(s: &str) -> impl Iterator<Item = &str> {
+    TAG_REGEX
+        .captures_iter(s)
+        .filter_map(|caps| caps.get(1))
+        .map(|m| m.as_str())
 }
 
 impl TimeSheet {
-    pub fn open(date: Date) -> Self {
-        let path = storage_path_for(date);
-        let times = read_times(&path).unwrap_or_default();
+    pub fn open(date: Date, config: &Rc<Config>) -> Result<Self, TraccError> {
+        Self::open_sheet(date, config, None)
+    }
+
+    pub fn open_sheet(
+        date: Date,
+        config: &Rc<Config>,
+        sheet: Option<&str>,
+    ) -> Result<Self, TraccError> {
+        Self::open_file(storage_path_for(date, config, sheet), Some(date), config)
+    }
+
+    /**
+     * A missing file is a new, empty sheet.
+     * A file that exists but can’t be read or parsed is an error,
+     * so it never gets replaced by an empty sheet on the next save.
+     */
+    pub fn open_file(
+        path: PathBuf,
+        date: Option<Date>,
+        config: &Rc<Config>,
+    ) -> Result<Self, TraccError> {
+        match Self::load(path.clone(), date, config) {
+            Err(err) if err.is_not_found() => {
+                let date = date.unwrap_or_else(|| file_date(&path, &[]));
+                Ok(Self::with_times(path, date, Vec::new(), config))
+            }
+            result => result,
+        }
+    }
+
+    /**
+     * Like `open_file`, but a missing file is an error too, `TraccError::NotFound`.
+     * A `date` of `None`, for files given by the user, takes the day from the file; see `file_date`.
+     */
+    pub fn load(
+        path: PathBuf,
+        date: Option<Date>,
+        config: &Rc<Config>,
+    ) -> Result<Self, TraccError> {
+        let file = fs::File::open(&path).map_err(|err| TraccError::io(path.clone(), err))?;
+        Self::read(file, path, date, config)
+    }
+
+    /// Like `load`, but from any reader, e.g. stdin; `path` is where `save` would write.
+    pub fn read(
+        reader: impl io::Read,
+        path: PathBuf,
+        date: Option<Date>,
+        config: &Rc<Config>,
+    ) -> Result<Self, TraccError> {
+        let (date, mut times) = read_times(reader, &path, date)?;
+        // The summaries rely on the order, which a hand-edited file may not keep.
+        let reordered = !times.is_sorted_by_key(TimePoint::total_seconds);
+        times.sort_by_key(TimePoint::total_seconds);
+        Ok(Self {
+            reordered_on_load: reordered,
+            ..Self::with_times(path, date, times, config)
+        })
+    }
+
+    fn with_times(path: PathBuf, date: Date, times: Vec<TimePoint>, config: &Rc<Config>) -> Self {
         let selected = times.len().saturating_sub(1);
         Self {
             date,
+            path,
             times,
             selected,
             register: None,
+            config: Rc::clone(config),
+            reordered_on_load: false,
+        }
+    }
+
+    /// Selects the entry that was selected when the sheet was last left, if it still exists.
+    pub fn restore_selection(&mut self) {
+        if let Some(selected) = read_selections(&self.config).get(&self.path) {
+            self.selected = (*selected).min(self.times.len().saturating_sub(1));
+        }
+    }
+
+    /// Remembers the selection for `restore_selection`.
+    pub fn remember_selection(&self) -> io::Result<()> {
+        let mut selections = read_selections(&self.config);
+        selections.insert(self.path.clone(), self.selected);
+        write_json_atomically(&selections_path(&self.config), &selections)
+    }
+
+    /**
+     * Write to a temporary file next to the sheet and rename it over the sheet,
+     * so a crash mid-write never leaves a truncated file behind.
+     */
+    pub fn save(&self) -> io::Result<()> {
+        write_json_atomically(&self.path, &self.stored())
+    }
+
+    /// The entries as they are written to disk.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(&self.stored())
+    }
+
+    /// Exactly what `save` writes, formatting included.
+    pub fn to_saved_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.stored())
+    }
+
+    fn stored(&self) -> Vec<StoredTimePoint> {
+        self.times
+            .iter()
+            .map(|tp| tp.to_stored(self.date))
+            .collect()
+    }
+
+    /**
+     * Copy the file on disk to `<path>.YYYYMMDD-HHMMSS.bak`
+     * and delete all but the newest `keep` backups of it.
+     */
+    pub fn back_up(&self, keep: usize) -> io::Result<()> {
+        if keep == 0 || !self.path.exists() {
+            return Ok(());
         }
+        let stamp = now()
+            .format(format_description!(
+                "[year][month][day]-[hour][minute][second]"
+            ))
+            .map_err(io::Error::other)?;
+        let name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        fs::copy(
+            &self.path,
+            self.path.with_file_name(format!("{}.{}.bak", name, stamp)),
+        )?;
+
+        let Some(dir) = self.path.parent() else {
+            return Ok(());
+        };
+        let prefix = format!("{}.", name);
+        let backups: Vec<_> = fs::read_dir(dir)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .map(|file| file.to_string_lossy())
+                    .map(|file| file.starts_with(&prefix) && file.ends_with(".bak"))
+                    .unwrap_or(false)
+            })
+            // The timestamps sort lexicographically, so this is oldest first.
+            .sorted()
+            .collect();
+        for old in &backups[..backups.len().saturating_sub(keep)] {
+            fs::remove_file(old)?;
+        }
+        Ok(())
     }
 
     pub fn current_date() -> Date {
@@ -303,18 +918,88 @@ impl TimeSheet {
         }
     }
 
-    pub fn printable(&self) -> Vec<String> {
-        self.times.iter().map(TimePoint::to_string).collect()
+    /// The entry the current moment falls into on today's sheet, i.e. the last one not after it.
+    pub fn now_index(&self) -> Option<usize> {
+        if !self.is_today() {
+            return None;
+        }
+        self.now_index_at(&self.now_point(END_TEXT))
+    }
+
+    fn now_index_at(&self, now: &TimePoint) -> Option<usize> {
+        self.times
+            .iter()
+            .rposition(|tp| tp.total_seconds() <= now.total_seconds())
     }
 
-    pub fn printable_with_preview(&self, preview: Option<(usize, TimePoint)>) -> Vec<String> {
-        let mut items = self.printable();
+    /**
+     * The list rows, each paired with whether it is a pause.
+     * With `durations`, each row ends with how long it lasted, or so far for the running one.
+     * On today's sheet, the row the current moment falls into ends with `NOW_MARKER`.
+     */
+    pub fn printable_with_preview(
+        &self,
+        preview: Option<(usize, TimePoint)>,
+        durations: bool,
+    ) -> Vec<(String, bool)> {
+        let row = |point: &TimePoint| {
+            (
+                point.format_with(&self.config.time_format),
+                self.is_muted(point),
+            )
+        };
+        let mut items: Vec<_> = self.times.iter().map(row).collect();
+        if durations {
+            let durations = self.entry_durations_at(&self.summary_end());
+            for ((text, _), duration) in items.iter_mut().zip(durations) {
+                if let Some(duration) = duration {
+                    *text += &format!(" ({})", self.duration_str(&duration));
+                }
+            }
+        }
+        if let Some((text, _)) = self.now_index().and_then(|index| items.get_mut(index)) {
+            *text += NOW_MARKER;
+        }
         if let Some((index, item)) = preview {
-            items.insert(index.min(items.len()), item.to_string());
+            items.insert(index.min(items.len()), row(&item));
         }
         items
     }
 
+    /// The window of each entry, the same ones `grouped_times` adds up.
+    /// Comments have none, and the last entry only while it runs.
+    fn entry_durations_at(&self, now: &TimePoint) -> Vec<Option<Duration>> {
+        let mut windows = self
+            .tracked()
+            .chain(self.maybe_end_time(now))
+            .tuple_windows()
+            .map(|(start, end)| Duration::seconds(end.total_seconds() - start.total_seconds()));
+        self.times
+            .iter()
+            .map(|tp| {
+                if is_comment(&tp.text) {
+                    None
+                } else {
+                    windows.next()
+                }
+            })
+            .collect()
+    }
+
+    /// The entries that count, i.e. all but comments.
+    fn tracked(&self) -> impl DoubleEndedIterator<Item = &TimePoint> + Clone {
+        self.times.iter().filter(|tp| !is_comment(&tp.text))
+    }
+
+    fn is_pause(&self, point: &TimePoint) -> bool {
+        effective_text(point.text.clone(), &self.config) == self.config.pause_label
+    }
+
+    /// Pauses and comments are shown muted in the list.
+    fn is_muted(&self, point: &TimePoint) -> bool {
+        self.is_pause(point) || is_comment(&point.text)
+    }
+
     pub fn selected_index_with_preview(&self, preview_index: Option<usize>) -> Option<usize> {
         match (self.selected_index(), preview_index) {
             (Some(selected), Some(index)) if index <= selected => Some(selected + 1),
@@ -337,25 +1022,51 @@ impl TimeSheet {
         current_minutes_since(self.date)
     }
 
-    pub fn set_selected_text(&mut self, text: String) {
+    /// A typed time as `parse_time_input` reads it, with `+` / `-` counting from entry `base`.
+    pub fn parse_time_from(&self, value: &str, base: Option<usize>) -> Result<i64, String> {
+        let base = base
+            .and_then(|index| self.times.get(index))
+            .map(TimePoint::time);
+        parse_time_input(value, self.current_minutes_since_start(), base)
+    }
+
+    /// The selected entry's text and note, as they are edited.
+    pub fn selected_input_text(&self) -> Option<String> {
+        self.selected_index()
+            .map(|index| self.times[index].input_text())
+    }
+
+    /// Sets the selected entry's text and note from typed input.
+    pub fn set_selected_input(&mut self, input: &str) {
         if self.times.is_empty() {
             return;
         }
-        self.times[self.selected].text = text;
+        let (text, note) = split_note(input);
+        let tp = &mut self.times[self.selected];
+        tp.text = text.to_string();
+        tp.note = note;
     }
 
     pub fn set_selected_time(&mut self, time: i64) {
+        self.set_selected_clock(time, 0);
+    }
+
+    fn set_selected_clock(&mut self, time: i64, seconds: i64) {
         if self.times.is_empty() {
             return;
         }
         self.times[self.selected].time = time;
+        self.times[self.selected].seconds = seconds;
         let timepoint = self.times[self.selected].clone();
-        self.times.sort_by_key(|tp| tp.time);
+        self.times.sort_by_key(TimePoint::total_seconds);
         self.selected = self.times.iter().position(|tp| tp == &timepoint).unwrap();
     }
 
     pub fn insertion_index_for_now(&self) -> usize {
-        let time = self.current_minutes_since_start();
+        self.insertion_index_for(self.current_minutes_since_start())
+    }
+
+    pub fn insertion_index_for(&self, time: i64) -> usize {
         self.times.partition_point(|tp| tp.time <= time)
     }
 
@@ -380,42 +1091,382 @@ impl TimeSheet {
         self.selected = (self.selected + 1).min(self.times.len().saturating_sub(1));
     }
 
-    pub fn selection_last(&mut self) {
+    /// Moves the selection `rows` down (or up when negative), stopping at either end.
+    pub fn selection_by(&mut self, rows: isize) {
         if self.times.is_empty() {
             return;
         }
-        self.selected = self.times.len().saturating_sub(1);
+        let last = self.times.len() - 1;
+        self.selected = self
+            .selected
+            .min(last)
+            .saturating_add_signed(rows)
+            .min(last);
     }
 
-    pub fn insert_at(&mut self, item: TimePoint, index: usize) {
-        let index = index.min(self.times.len());
-        if index == self.times.len() {
-            self.times.push(item);
-            self.selected = self.times.len() - 1;
-        } else {
-            self.times.insert(index, item);
-            self.selected = index;
+    /**
+     * The entries whose text or note contains `term`, ignoring case, in list order.
+     * With `fuzzy_search`, also those containing its letters in order,
+     * ranked by `fuzzy_score` instead.
+     */
+    pub fn matching(&self, term: &str) -> Vec<usize> {
+        let term = term.to_lowercase();
+        if !self.config.fuzzy_search {
+            return self
+                .times
+                .iter()
+                .enumerate()
+                .filter(|(_, tp)| tp.input_text().to_lowercase().contains(&term))
+                .map(|(index, _)| index)
+                .collect();
         }
+        self.times
+            .iter()
+            .enumerate()
+            .filter_map(|(index, tp)| {
+                fuzzy_score(&tp.input_text().to_lowercase(), &term).map(|score| (index, score))
+            })
+            // Stable, so equally good matches stay in list order.
+            .sorted_by(|(_, a), (_, b)| b.cmp(a))
+            .map(|(index, _)| index)
+            .collect()
     }
 
-    pub fn remove_current(&mut self) {
-        if self.times.is_empty() {
-            return;
+    /// Selects the first match of a new search: the best one with `fuzzy_search`,
+    /// otherwise the next one below the selection.
+    pub fn select_best_match(&mut self, term: &str) {
+        if !self.config.fuzzy_search {
+            return self.select_match(term, true);
         }
-        let index = self.selected;
-        self.selected = index.min(self.times.len().saturating_sub(2));
+        if let Some(&best) = self.matching(term).first() {
+            self.selected = best;
+        }
+    }
+
+    /**
+     * Selects the next (or previous) entry matching `term`, wrapping around at the ends.
+     * With `fuzzy_search`, that is the next worse (or better) match instead of the next row.
+     */
+    pub fn select_match(&mut self, term: &str, forward: bool) {
+        let matches = self.matching(term);
+        if self.config.fuzzy_search && !matches.is_empty() {
+            let rank = matches.iter().position(|&index| index == self.selected);
+            let len = matches.len();
+            self.selected = match (rank, forward) {
+                (Some(rank), true) => matches[(rank + 1) % len],
+                (Some(rank), false) => matches[(rank + len - 1) % len],
+                (None, true) => matches[0],
+                (None, false) => matches[len - 1],
+            };
+            return;
+        }
+        let next = if forward {
+            matches
+                .iter()
+                .find(|&&index| index > self.selected)
+                .or(matches.first())
+        } else {
+            matches
+                .iter()
+                .rev()
+                .find(|&&index| index < self.selected)
+                .or(matches.last())
+        };
+        if let Some(&index) = next {
+            self.selected = index;
+        }
+    }
+
+    pub fn selection_last(&mut self) {
+        if self.times.is_empty() {
+            return;
+        }
+        self.selected = self.times.len().saturating_sub(1);
+    }
+
+    /**
+     * Swap the selected entry with the one above it.
+     * The moved entry takes over its neighbour's time so the order survives the next sort.
+     */
+    pub fn move_up(&mut self) {
+        let index = self.selected;
+        if index == 0 || index >= self.times.len() {
+            return;
+        }
+        self.times[index].time = self.times[index - 1].time;
+        self.times[index].seconds = self.times[index - 1].seconds;
+        self.times.swap(index - 1, index);
+        self.selected = index - 1;
+    }
+
+    pub fn move_down(&mut self) {
+        let index = self.selected;
+        if index + 1 >= self.times.len() {
+            return;
+        }
+        self.times[index].time = self.times[index + 1].time;
+        self.times[index].seconds = self.times[index + 1].seconds;
+        self.times.swap(index, index + 1);
+        self.selected = index + 1;
+    }
+
+    pub fn insert_at(&mut self, item: TimePoint, index: usize) {
+        let index = index.min(self.times.len());
+        if index == self.times.len() {
+            self.times.push(item);
+            self.selected = self.times.len() - 1;
+        } else {
+            self.times.insert(index, item);
+            self.selected = index;
+        }
+    }
+
+    pub fn remove_current(&mut self) {
+        if self.times.is_empty() {
+            return;
+        }
+        let index = self.selected;
+        self.selected = index.min(self.times.len().saturating_sub(2));
         self.register = self.times.remove(index).into();
     }
 
+    /// Removes the entries `first..=last` and selects the one after them; the register is kept.
+    pub fn remove_range(&mut self, first: usize, last: usize) {
+        let last = last.min(self.times.len().saturating_sub(1));
+        if first > last || self.times.is_empty() {
+            return;
+        }
+        self.times.drain(first..=last);
+        self.selected = first.min(self.times.len().saturating_sub(1));
+    }
+
+    /// Like `shift_following`, but only the entries `first..=last` move.
+    pub fn shift_range(&mut self, first: usize, last: usize, minutes: i64) {
+        let last = last.min(self.times.len().saturating_sub(1));
+        if first > last || self.times.is_empty() {
+            return;
+        }
+        let earliest = self.times[first..=last]
+            .iter()
+            .map(|tp| tp.time)
+            .min()
+            .unwrap_or(0);
+        let minutes = minutes.max(-earliest);
+        for tp in &mut self.times[first..=last] {
+            tp.time += minutes;
+        }
+        let timepoint = self.times[self.selected].clone();
+        self.times.sort_by_key(TimePoint::total_seconds);
+        self.selected = self.times.iter().position(|tp| tp == &timepoint).unwrap();
+    }
+
+    /**
+     * Replaces the text of every entry whose text is `from` with `to` and returns how many changed.
+     * With `effective`, `from` is compared against the effective text instead,
+     * so tagged and `[overridden]` variants are renamed too.
+     */
+    pub fn rename_all(&mut self, from: &str, to: &str, effective: bool) -> usize {
+        let config = Rc::clone(&self.config);
+        let mut renamed = 0;
+        for tp in self.times.iter_mut() {
+            let matches = if effective {
+                effective_text(tp.text.clone(), &config) == from
+            } else {
+                tp.text == from
+            };
+            if matches {
+                tp.text = to.to_string();
+                renamed += 1;
+            }
+        }
+        renamed
+    }
+
+    pub fn selected_effective_text(&self) -> Option<String> {
+        self.selected_text()
+            .map(|text| effective_text(text, &self.config))
+    }
+
+    /// Whether `time` lies strictly between the selected entry and the next one.
+    pub fn can_split_at(&self, time: i64) -> bool {
+        let index = match self.selected_index() {
+            Some(index) => index,
+            None => return false,
+        };
+        time > self.times[index].time
+            && self
+                .times
+                .get(index + 1)
+                .is_none_or(|next| time < next.time)
+    }
+
+    /// How many entries right after the selected one share its effective text.
+    pub fn mergeable_count(&self) -> usize {
+        let index = match self.selected_index() {
+            Some(index) => index,
+            None => return 0,
+        };
+        let text = effective_text(self.times[index].text.clone(), &self.config);
+        self.times[index + 1..]
+            .iter()
+            .take_while(|tp| effective_text(tp.text.clone(), &self.config) == text)
+            .count()
+    }
+
+    /// Absorbs the entries counted by `mergeable_count` into the selected one,
+    /// which keeps its time and text.
+    pub fn merge_following(&mut self) {
+        let count = self.mergeable_count();
+        if count > 0 {
+            let index = self.selected;
+            self.times.drain(index + 1..=index + count);
+        }
+    }
+
     pub fn paste(&mut self) {
         if let Some(item) = self.register.clone() {
+            let now = self.now_point("");
             let mut item = item;
-            item.time = self.current_minutes_since_start();
+            item.time = now.time;
+            item.seconds = now.seconds;
+            let index = self.insertion_index_for_now();
+            self.insert_at(item, index);
+        }
+    }
+
+    /// Pastes the register unchanged, at the position its own time sorts to.
+    pub fn paste_in_place(&mut self) {
+        if let Some(item) = self.register.clone() {
+            let index = self.insertion_index_for(item.time);
+            self.insert_at(item, index);
+        }
+    }
+
+    /**
+     * Adds a copy of the selected entry, text, overrides and note included, at the current time,
+     * rounded down like `snap_current_to_now` does. The register is left alone.
+     */
+    pub fn duplicate_selected(&mut self, round_to: i64) {
+        let Some(selected) = self.times.get(self.selected) else {
+            return;
+        };
+        let now = self.now_point("");
+        let mut item = selected.clone();
+        if self.config.seconds {
+            item.time = now.time;
+            item.seconds = now.seconds;
+        } else {
+            item.time = now.time - now.time.rem_euclid(round_to.max(1));
+            item.seconds = 0;
+        }
+        self.insert_in_order(item);
+    }
+
+    /// Appends an explicit "end" entry at the current time,
+    /// which stops the last task from running on until now.
+    pub fn clock_out(&mut self) {
+        let item = self.now_point(END_TEXT);
+        let index = self.insertion_index_for_now();
+        self.insert_at(item, index);
+    }
+
+    /**
+     * Distinct entry texts starting with `prefix`, ignoring case, the latest first.
+     * The text inside an `[override]` counts too, so "rev" finds "code review [review]".
+     */
+    pub fn completions(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.to_lowercase();
+        let mut seen = collections::HashSet::new();
+        self.times
+            .iter()
+            .rev()
+            .map(|tp| &tp.text)
+            .filter(|text| {
+                text.to_lowercase().starts_with(&prefix)
+                    || override_groups(text).is_some_and(|groups| {
+                        groups
+                            .iter()
+                            .any(|group| group.to_lowercase().starts_with(&prefix))
+                    })
+            })
+            .filter(|text| seen.insert(text.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// The raw text of the latest entry that isn’t a pause or `end`.
+    pub fn last_task_text(&self) -> Option<&str> {
+        self.tracked()
+            .rev()
+            .find(|tp| !self.is_pause(tp) && tp.text.trim() != END_TEXT)
+            .map(|tp| tp.text.as_str())
+    }
+
+    /// Appends the last task again at the current time, e.g. to resume it after a break.
+    pub fn repeat_last_task(&mut self) {
+        if let Some(text) = self.last_task_text() {
+            let item = self.now_point(text);
             let index = self.insertion_index_for_now();
             self.insert_at(item, index);
         }
     }
 
+    /**
+     * Starts a pause now, or ends the running one by resuming the last task.
+     * Returns whether anything was added; a pause with no task before it can’t be ended.
+     */
+    pub fn toggle_break(&mut self) -> bool {
+        if self.on_break_at(&self.now_point(END_TEXT)) {
+            let resumable = self.last_task_text().is_some();
+            self.repeat_last_task();
+            resumable
+        } else {
+            self.start_break();
+            true
+        }
+    }
+
+    /// Appends a `pause_label` entry at the current time.
+    pub fn start_break(&mut self) {
+        let item = self.now_point(&self.config.pause_label);
+        let index = self.insertion_index_for_now();
+        self.insert_at(item, index);
+    }
+
+    /// Whether the latest entry that already started is a pause.
+    fn on_break_at(&self, now: &TimePoint) -> bool {
+        self.tracked()
+            .rev()
+            .find(|tp| tp.total_seconds() <= now.total_seconds())
+            .is_some_and(|tp| self.is_pause(tp))
+    }
+
+    /// An entry at the current time, to the second with the `seconds` setting.
+    pub fn now_point(&self, text: &str) -> TimePoint {
+        let (minutes, seconds) = current_clock_since(self.date);
+        let seconds = if self.config.seconds { seconds } else { 0 };
+        TimePoint::new(text, minutes).with_seconds(seconds)
+    }
+
+    /**
+     * Where the summaries end the last entry: now on today's sheet (or a later one, which
+     * hasn't started), but at the last entry on a past day, which may just lack its `end`.
+     * Running those into the current time would count every day since as work.
+     */
+    fn summary_end(&self) -> TimePoint {
+        match self.tracked().last() {
+            Some(last) if self.date < today() => {
+                TimePoint::new(END_TEXT, last.time).with_seconds(last.seconds())
+            }
+            _ => self.now_point(END_TEXT),
+        }
+    }
+
+    /// Whether `minutes` (since the sheet's midnight) is later than the current minute.
+    pub fn is_in_future(&self, minutes: i64) -> bool {
+        minutes > self.now_point("").time
+    }
+
     pub fn can_paste(&self) -> bool {
         self.register.is_some()
     }
@@ -429,21 +1480,72 @@ impl TimeSheet {
     }
 
     /**
-     * Adjust the current time by `minutes` and round the result to a multiple of `minutes`.
+     * Adjust the current time by `minutes` and round the result down to a multiple of `round_to`.
      * This is so I can adjust in steps of 5 but still get nice, even numbers in the output.
+     * A `round_to` of 1 disables rounding.
      */
-    pub fn shift_current(&mut self, minutes: i64) {
+    pub fn shift_current(&mut self, minutes: i64, round_to: i64) {
         if self.times.is_empty() {
             return;
         }
         let time = &mut self.times[self.selected].time;
         *time += minutes;
-        *time -= time.rem_euclid(5);
+        *time -= time.rem_euclid(round_to.max(1));
+        let timepoint = self.times[self.selected].clone();
+        self.times.sort_by_key(TimePoint::total_seconds);
+        self.selected = self.times.iter().position(|tp| tp == &timepoint).unwrap();
+    }
+
+    /**
+     * Round every entry to the nearest multiple of `step` minutes, dropping seconds.
+     * An entry that would land on or before the one above it is pushed one step further,
+     * so rounding never makes a block disappear; the order stays as it was.
+     */
+    pub fn round_all(&mut self, step: i64) {
+        let step = step.max(1);
+        let mut previous: Option<(i64, i64)> = None;
+        for tp in &mut self.times {
+            let original = tp.total_seconds();
+            let mut rounded = (original + step * 30).div_euclid(step * 60) * step;
+            if let Some((previous_original, previous_rounded)) = previous {
+                if original > previous_original && rounded <= previous_rounded {
+                    rounded = previous_rounded + step;
+                }
+            }
+            tp.time = rounded;
+            tp.seconds = 0;
+            previous = Some((original, rounded));
+        }
+    }
+
+    /**
+     * Move the selected entry and every later one by `minutes`, keeping their spacing.
+     * Unlike `shift_current` nothing is rounded. Entries never move before midnight.
+     */
+    pub fn shift_following(&mut self, minutes: i64) {
+        if self.times.is_empty() {
+            return;
+        }
+        let minutes = minutes.max(-self.times[self.selected].time);
+        for tp in &mut self.times[self.selected..] {
+            tp.time += minutes;
+        }
         let timepoint = self.times[self.selected].clone();
-        self.times.sort_by_key(|tp| tp.time);
+        self.times.sort_by_key(TimePoint::total_seconds);
         self.selected = self.times.iter().position(|tp| tp == &timepoint).unwrap();
     }
 
+    /// Set the selected time to now, rounded down like `shift_current` does.
+    /// With the `seconds` setting it is set to the exact second instead.
+    pub fn snap_current_to_now(&mut self, round_to: i64) {
+        let now = self.now_point("");
+        if self.config.seconds {
+            self.set_selected_clock(now.time, now.seconds);
+        } else {
+            self.set_selected_time(now.time - now.time.rem_euclid(round_to.max(1)));
+        }
+    }
+
     pub fn has_time_overflow(&self) -> bool {
         self.times
             .last()
@@ -451,65 +1553,2174 @@ impl TimeSheet {
             .unwrap_or(false)
     }
 
-    fn grouped_times(&self) -> collections::BTreeMap<String, Duration> {
-        let last_time = self.times.last();
-        let current_time = self.current_minutes_since_start();
-        self.times
-            .iter()
-            .chain(TimeSheet::maybe_end_time(last_time, current_time).iter())
+    /// Hints the summary shows below the pause time.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.has_time_overflow() {
+            warnings.push("tracking exceeds day".to_string());
+        }
+        if self.reordered_on_load {
+            warnings.push("entries were sorted by time".to_string());
+        }
+        match self.duplicate_times() {
+            0 => {}
+            1 => warnings.push("1 entry starts with the one before it".to_string()),
+            count => warnings.push(format!("{} entries start with the one before them", count)),
+        }
+        warnings
+    }
+
+    /// Entries at the same time as the previous one, which makes that one zero-length.
+    fn duplicate_times(&self) -> usize {
+        self.tracked()
             .tuple_windows()
-            .map(|(prev, next)| (prev.text.clone(), Duration::minutes(next.time - prev.time)))
+            .filter(|(prev, next)| prev.total_seconds() == next.total_seconds())
+            .count()
+    }
+
+    /// The time per task, with the pauses under `pause_label`.
+    pub fn grouped_times(&self) -> collections::BTreeMap<String, Duration> {
+        self.grouped_times_at(&self.summary_end())
+    }
+
+    fn grouped_times_at(&self, now: &TimePoint) -> collections::BTreeMap<String, Duration> {
+        self.group(self.windows_at(now))
+    }
+
+    fn group(&self, windows: Vec<(String, Duration)>) -> collections::BTreeMap<String, Duration> {
+        windows
+            .into_iter()
             // Fold into a map to group by description.
             // I use a BTreeMap because I need a stable output order for the iterator
             // (otherwise the summary list will jump around on every input).
             .fold(collections::BTreeMap::new(), |mut map, (text, duration)| {
-                *map.entry(effective_text(text)).or_insert(Duration::ZERO) += duration;
+                *map.entry(effective_text(text, &self.config))
+                    .or_insert(Duration::ZERO) += duration;
                 map
             })
     }
 
-    fn maybe_end_time(last_time: Option<&TimePoint>, current_time: i64) -> Option<TimePoint> {
-        match last_time {
-            Some(tp) if PAUSE_TEXTS.contains(&&tp.text[..]) => None,
-            Some(tp) if tp.text == END_TEXT => None,
-            Some(tp) if tp.time > current_time => None,
-            _ => Some(TimePoint::new(END_TEXT, current_time)),
+    /**
+     * The working time in each hour of the day, with every block split at the full hours.
+     * Pauses are left out; time past midnight counts towards the early hours.
+     */
+    pub fn minutes_by_hour(&self) -> [Duration; 24] {
+        self.minutes_by_hour_at(&self.summary_end())
+    }
+
+    fn minutes_by_hour_at(&self, now: &TimePoint) -> [Duration; 24] {
+        let mut hours = [Duration::ZERO; 24];
+        for (prev, next) in self
+            .tracked()
+            .chain(self.maybe_end_time(now))
+            .tuple_windows()
+        {
+            if self.is_pause(prev) {
+                continue;
+            }
+            let mut start = prev.total_seconds();
+            let end = next.total_seconds();
+            while start < end {
+                let hour_end = (start / 3600 + 1) * 3600;
+                let until = hour_end.min(end);
+                hours[(start / 3600 % 24) as usize] += Duration::seconds(until - start);
+                start = until;
+            }
+        }
+        hours
+    }
+
+    /// The raw text of every entry with the time until the next one (or until now).
+    fn windows_at(&self, now: &TimePoint) -> Vec<(String, Duration)> {
+        self.windows_where(now, |_| true)
+    }
+
+    /// Like `windows_at`, but only for the entries `keep` is true for.
+    fn windows_where(
+        &self,
+        now: &TimePoint,
+        keep: impl Fn(&TimePoint) -> bool,
+    ) -> Vec<(String, Duration)> {
+        self.tracked()
+            .chain(self.maybe_end_time(now))
+            .tuple_windows()
+            .filter(|(prev, _)| keep(prev))
+            .map(|(prev, next)| {
+                let duration = Duration::seconds(next.total_seconds() - prev.total_seconds());
+                (prev.text.clone(), duration)
+            })
+            .collect()
+    }
+
+    /// Like `grouped_times`, but keyed by `#tag`.
+    /// An entry with several tags counts fully towards each of them.
+    fn tagged_times_at(&self, now: &TimePoint) -> collections::BTreeMap<String, Duration> {
+        self.windows_at(now).into_iter().fold(
+            collections::BTreeMap::new(),
+            |mut map, (text, duration)| {
+                for tag in tags(&text) {
+                    *map.entry(tag.to_string()).or_insert(Duration::ZERO) += duration;
+                }
+                map
+            },
+        )
+    }
+
+    /// The working time per project code, with the entries that have none last.
+    fn project_times_at(&self, now: &TimePoint) -> Vec<(String, Duration)> {
+        let mut projects = collections::BTreeMap::new();
+        for (prev, next) in self
+            .tracked()
+            .chain(self.maybe_end_time(now))
+            .tuple_windows()
+        {
+            if effective_text(prev.text.clone(), &self.config) == self.config.pause_label {
+                continue;
+            }
+            let duration = Duration::seconds(next.total_seconds() - prev.total_seconds());
+            // `None` sorts first in the map, so it is moved to the end below.
+            *projects
+                .entry(prev.project.clone())
+                .or_insert(Duration::ZERO) += duration;
+        }
+        let unassigned = projects.remove(&None);
+        projects
+            .into_iter()
+            .map(|(project, duration)| (format!("@{}", project.unwrap_or_default()), duration))
+            .chain(unassigned.map(|duration| (UNASSIGNED_PROJECT.to_string(), duration)))
+            .collect()
+    }
+
+    /// `@project: H:MM` lines, or nothing while no entry has a project.
+    pub fn time_by_project(&self) -> String {
+        if self.times.iter().all(|tp| tp.project.is_none()) {
+            return String::new();
+        }
+        self.project_times_at(&self.summary_end())
+            .into_iter()
+            .map(|(project, duration)| format!("{}: {}", project, self.duration_str(&duration)))
+            .join("\n")
+    }
+
+    /// Sets the project of the selected entry; a blank code clears it.
+    pub fn set_selected_project(&mut self, project: &str) {
+        if let Some(tp) = self.times.get_mut(self.selected) {
+            let project = project.trim().trim_start_matches('@');
+            tp.project = (!project.is_empty()).then(|| project.to_string());
+        }
+    }
+
+    pub fn selected_project(&self) -> Option<&str> {
+        self.times.get(self.selected)?.project.as_deref()
+    }
+
+    pub fn time_by_tags(&self) -> String {
+        self.tagged_times_at(&self.summary_end())
+            .into_iter()
+            .map(|(tag, duration)| format!("#{}: {}", tag, self.duration_str(&duration)))
+            .join("\n")
+    }
+
+    /// The last entry, with how long it has been running, once that exceeds `long_running_minutes`.
+    /// Only today's sheet has a running entry; a past day may just lack its `end`.
+    pub fn long_running(&self) -> Option<(usize, Duration)> {
+        if !self.is_today() {
+            return None;
+        }
+        self.long_running_at(&self.now_point(END_TEXT))
+    }
+
+    fn long_running_at(&self, now: &TimePoint) -> Option<(usize, Duration)> {
+        let threshold = self.config.long_running_minutes;
+        if threshold == 0 {
+            return None;
+        }
+        let end = self.maybe_end_time(now)?;
+        let (index, last) = self
+            .times
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, tp)| !is_comment(&tp.text))?;
+        let running = Duration::seconds(end.total_seconds() - last.total_seconds());
+        (running > Duration::minutes(threshold as i64)).then_some((index, running))
+    }
+
+    /// `Task running for H:MM`, while `long_running` flags the last entry.
+    pub fn long_running_warning(&self) -> Option<String> {
+        let (index, running) = self.long_running()?;
+        Some(format!(
+            "{} running for {}: forgot a break?",
+            effective_text(self.times[index].text.clone(), &self.config),
+            self.duration_str(&running)
+        ))
+    }
+
+    /// `now` closes the last window unless the day was already ended or it starts at or after `now`.
+    fn maybe_end_time<'a>(&self, now: &'a TimePoint) -> Option<&'a TimePoint> {
+        match self.tracked().last() {
+            Some(tp) if self.is_pause(tp) => None,
+            Some(tp) if tp.text.trim() == END_TEXT => None,
+            Some(tp) if tp.total_seconds() >= now.total_seconds() => None,
+            _ => Some(now),
         }
     }
 
+    /// Inserts `item` after the entries at or before its time.
+    pub fn insert_in_order(&mut self, item: TimePoint) {
+        let index = self.insertion_index_for(item.time);
+        self.insert_at(item, index);
+    }
+
+    /// The task being worked on right now, if the latest started entry isn’t a pause.
+    pub fn current_task(&self) -> Option<String> {
+        self.current_task_at(&self.now_point(END_TEXT))
+    }
+
+    /// The running task with how long its entry has been running.
+    fn running_at(&self, now: &TimePoint) -> Option<(String, Duration)> {
+        let start = self
+            .tracked()
+            .rev()
+            .find(|tp| tp.total_seconds() <= now.total_seconds())?;
+        let task = effective_text(start.text.clone(), &self.config);
+        let running = Duration::seconds(now.total_seconds() - start.total_seconds());
+        (task != self.config.pause_label && start.text.trim() != END_TEXT)
+            .then_some((task, running))
+    }
+
+    /**
+     * `status_format` with `{task}`, `{running}` (since its entry started), `{total}`
+     * and `{target}` (what is left or the overtime) filled in,
+     * or `status_idle_format` while nothing runs.
+     */
+    pub fn status_line(&self) -> String {
+        self.status_line_at(&self.now_point(END_TEXT))
+    }
+
+    fn status_line_at(&self, now: &TimePoint) -> String {
+        let total = self.duration_str(&self.working_total_at(now));
+        let target = self.target_status().unwrap_or_default();
+        let (template, task, running) = match self.running_at(now) {
+            Some((task, running)) => (
+                &self.config.status_format,
+                task,
+                self.duration_str(&running),
+            ),
+            None => (
+                &self.config.status_idle_format,
+                String::new(),
+                String::new(),
+            ),
+        };
+        template
+            .replace("{task}", &task)
+            .replace("{running}", &running)
+            .replace("{total}", &total)
+            .replace("{target}", &target)
+    }
+
+    fn current_task_at(&self, now: &TimePoint) -> Option<String> {
+        self.tracked()
+            .rev()
+            .find(|tp| tp.total_seconds() <= now.total_seconds())
+            .map(|tp| effective_text(tp.text.clone(), &self.config))
+            .filter(|text| *text != self.config.pause_label)
+    }
+
     pub fn time_by_tasks(&self) -> String {
-        self.grouped_times()
+        self.task_lines(false, false)
+    }
+
+    /// The task lines followed by `Sum: H:MM`, as `--report` prints them.
+    pub fn plain_summary(&self) -> String {
+        let tasks = self.time_by_tasks();
+        let sum = format!("Sum: {}", self.sum_as_str());
+        if tasks.is_empty() {
+            sum
+        } else {
+            format!("{}\n{}", tasks, sum)
+        }
+    }
+
+    /**
+     * One `task: H:MM` line per task, optionally followed by its share of the working total.
+     * `by_duration` puts the longest first (ties alphabetically) and includes pause.
+     * Pause gets no percentage since it isn’t part of that total.
+     */
+    pub fn task_lines(&self, by_duration: bool, percentages: bool) -> String {
+        let grouped = self.grouped_times();
+        let total = self.working_total().whole_minutes();
+        let lines: Vec<_> = if by_duration {
+            grouped
+                .into_iter()
+                // The map is already alphabetical and the sort is stable, which breaks ties.
+                .sorted_by(|(_, a), (_, b)| b.cmp(a))
+                .collect()
+        } else {
+            self.task_durations()
+        };
+        lines
             .into_iter()
-            .filter(|(text, _)| text != MAIN_PAUSE_TEXT)
-            .map(|(text, duration)| format!("{}: {}", text, format_duration(&duration)))
+            .map(|(text, duration)| {
+                let share = if percentages && total > 0 && text != self.config.pause_label {
+                    let percent = (duration.whole_minutes() * 100 + total / 2) / total;
+                    format!(" ({}%)", percent)
+                } else {
+                    String::new()
+                };
+                format!("{}: {}{}", text, self.duration_str(&duration), share)
+            })
             .join("\n")
     }
 
-    pub fn sum_as_str(&self) -> String {
-        let total = self
+    /**
+     * The time per task without pause; the data behind `time_by_tasks`.
+     * Tasks listed in `task_order` come first in that order, the rest alphabetically.
+     * Use `pause_total` for the pause.
+     */
+    pub fn task_durations(&self) -> Vec<(String, Duration)> {
+        let tasks = self
             .grouped_times()
             .into_iter()
-            .filter(|(text, _)| text != MAIN_PAUSE_TEXT)
-            .fold(Duration::ZERO, |total, (_, d)| total + d);
-        format_duration(&total)
+            .filter(|(text, _)| *text != self.config.pause_label);
+        in_task_order(tasks, &self.config)
     }
 
-    pub fn pause_time(&self) -> String {
-        let times = self.grouped_times();
-        let duration = times
-            .get(MAIN_PAUSE_TEXT)
-            .copied()
-            .unwrap_or(Duration::ZERO);
-        format!("{}: {}", MAIN_PAUSE_TEXT, format_duration(&duration))
+    /// The working tasks with each duration rounded up to the next quarter hour for invoicing.
+    /// Entries marked non-billable are left out.
+    fn billable_times(&self) -> Vec<(String, Duration)> {
+        self.billable_task_durations()
+            .into_iter()
+            .map(|(text, duration)| {
+                let minutes = duration.whole_minutes();
+                let rounded = (minutes + BILLING_STEP_MINUTES - 1).div_euclid(BILLING_STEP_MINUTES);
+                (text, Duration::minutes(rounded * BILLING_STEP_MINUTES))
+            })
+            .collect()
     }
-}
 
-fn format_duration(d: &Duration) -> String {
-    format!("{}:{:02}", d.whole_hours(), d.whole_minutes() % 60)
-}
+    fn billable_task_durations(&self) -> Vec<(String, Duration)> {
+        self.billable_task_durations_at(&self.summary_end())
+    }
 
-fn format_minutes(minutes: i64) -> String {
-    let hours = minutes.div_euclid(60);
-    let minutes = minutes.rem_euclid(60);
-    format!("{:02}:{:02}", hours, minutes)
+    fn billable_task_durations_at(&self, now: &TimePoint) -> Vec<(String, Duration)> {
+        let windows = self.windows_where(now, |tp| tp.billable);
+        let tasks = self
+            .group(windows)
+            .into_iter()
+            .filter(|(text, _)| *text != self.config.pause_label);
+        in_task_order(tasks, &self.config)
+    }
+
+    /// The working time of the entries that aren’t marked non-billable, unrounded.
+    pub fn billable_total(&self) -> Duration {
+        self.billable_task_durations()
+            .into_iter()
+            .fold(Duration::ZERO, |total, (_, d)| total + d)
+    }
+
+    /// The `billable_total` for the summary, or none while every entry is billable.
+    pub fn billable_total_str(&self) -> Option<String> {
+        self.times
+            .iter()
+            .any(|tp| !tp.billable)
+            .then(|| self.duration_str(&self.billable_total()))
+    }
+
+    /// Marks the selected entry as billable or not.
+    pub fn toggle_selected_billable(&mut self) {
+        if let Some(tp) = self.times.get_mut(self.selected) {
+            tp.billable = !tp.billable;
+        }
+    }
+
+    /// The indexes of the entries marked non-billable.
+    pub fn non_billable(&self) -> Vec<usize> {
+        self.times.iter().positions(|tp| !tp.billable).collect()
+    }
+
+    pub fn billable_time_by_tasks(&self) -> String {
+        self.billable_times()
+            .into_iter()
+            .map(|(text, duration)| format!("{}: {}", text, self.duration_str(&duration)))
+            .join("\n")
+    }
+
+    /// The sum of the rounded task durations, not the rounded raw total.
+    pub fn billable_sum_as_str(&self) -> String {
+        let total = self
+            .billable_times()
+            .into_iter()
+            .fold(Duration::ZERO, |total, (_, d)| total + d);
+        self.duration_str(&total)
+    }
+
+    /**
+     * Adds the `time,text` rows of a CSV file like `to_csv` writes and returns how many there were.
+     * Times may have seconds, and a text may end in a note after `NOTE_DELIMITER`.
+     * The header is optional; reading stops at the first blank line, where the task table starts.
+     * Nothing is added if any row is malformed.
+     */
+    pub fn import_csv<R: io::BufRead>(&mut self, reader: R) -> Result<usize, String> {
+        let mut imported = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let number = index + 1;
+            let line = line.map_err(|err| format!("line {}: {}", number, err))?;
+            if line.trim().is_empty() {
+                break;
+            }
+            let fields = csv_record(&line).map_err(|err| format!("line {}: {}", number, err))?;
+            if index == 0 && fields == ["time", "text"] {
+                continue;
+            }
+            let [time, text] = fields.as_slice() else {
+                return Err(format!(
+                    "line {}: expected 2 fields, found {}",
+                    number,
+                    fields.len()
+                ));
+            };
+            let (time, seconds) =
+                parse_clock(time).map_err(|err| format!("line {}: {}", number, err))?;
+            imported.push(TimePoint::from_input(text, time).with_seconds(seconds));
+        }
+        let count = imported.len();
+        self.times.extend(imported);
+        self.times.sort_by_key(TimePoint::total_seconds);
+        Ok(count)
+    }
+
+    /// All entries as `time,text` rows, with their notes, then a blank line and the per-task totals.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("time,text\n");
+        // Always 24-hour, so `import_csv` can read it back whatever `time_format` says.
+        let format = if self.config.seconds {
+            "%H:%M:%S"
+        } else {
+            "%H:%M"
+        };
+        for tp in &self.times {
+            let time = format_clock(tp.time, tp.seconds, format).unwrap();
+            csv += &format!("{},{}\n", csv_field(&time), csv_field(&tp.input_text()));
+        }
+        csv += "\ntask,duration\n";
+        for (text, duration) in self.grouped_times() {
+            csv += &format!("{},{}\n", csv_field(&text), self.duration_str(&duration));
+        }
+        csv
+    }
+
+    /**
+     * One JSON object per entry and line: its timestamp, text, effective text and
+     * the time until the next entry, as the summary counts it (`null` for comments and the end).
+     */
+    pub fn to_jsonl(&self) -> String {
+        self.to_jsonl_at(&self.summary_end())
+    }
+
+    fn to_jsonl_at(&self, now: &TimePoint) -> String {
+        self.times
+            .iter()
+            .zip(self.entry_durations_at(now))
+            .map(|(tp, duration)| {
+                let mut entry = serde_json::json!({
+                    "time": tp.datetime_on(self.date).format(&Rfc3339).unwrap_or_default(),
+                    "text": tp.text,
+                    "effective_text": effective_text(tp.text.clone(), &self.config),
+                    "duration": duration.map(|d| self.duration_str(&d)),
+                    "seconds": duration.map(|d| d.whole_seconds()),
+                });
+                if let Some(note) = &tp.note {
+                    entry["note"] = serde_json::json!(note);
+                }
+                entry.to_string() + "\n"
+            })
+            .collect()
+    }
+
+    /**
+     * Every block between two entries as an iCalendar `VEVENT`, named after its entry
+     * and timed in UTC. Pauses are skipped; the running task lasts until now.
+     */
+    pub fn to_ics(&self) -> String {
+        self.ics_at(&self.summary_end(), now())
+    }
+
+    fn ics_at(&self, now: &TimePoint, stamp: OffsetDateTime) -> String {
+        let utc = format_description!("[year][month][day]T[hour][minute][second]Z");
+        let format = |at: OffsetDateTime| at.to_offset(UtcOffset::UTC).format(utc).unwrap();
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//tracc//tracc//EN".to_string(),
+        ];
+        for (index, (start, end)) in self
+            .tracked()
+            .chain(self.maybe_end_time(now))
+            .tuple_windows()
+            .enumerate()
+        {
+            if self.is_pause(start) || end.total_seconds() <= start.total_seconds() {
+                continue;
+            }
+            lines.extend([
+                "BEGIN:VEVENT".to_string(),
+                format!("UID:{}-{}@tracc", self.date, index),
+                format!("DTSTAMP:{}", format(stamp)),
+                format!("DTSTART:{}", format(start.datetime_on(self.date))),
+                format!("DTEND:{}", format(end.datetime_on(self.date))),
+                format!("SUMMARY:{}", ics_text(&start.text)),
+                "END:VEVENT".to_string(),
+            ]);
+        }
+        lines.push("END:VCALENDAR".to_string());
+        lines.iter().map(|line| fold_ics_line(line)).collect()
+    }
+
+    /// The per-task summary as a GitHub-flavored Markdown table.
+    /// Pause gets its own italic row after the total so it doesn’t inflate it.
+    pub fn to_markdown(&self) -> String {
+        let mut table = String::from("| Task | Duration |\n| --- | ---: |\n");
+        for (text, duration) in self.task_durations() {
+            table += &format!(
+                "| {} | {} |\n",
+                text.replace('|', "\\|"),
+                self.duration_str(&duration)
+            );
+        }
+        table += &format!("| **Total** | **{}** |\n", self.sum_as_str());
+        let pause = self.pause_total();
+        table += &format!(
+            "| *{}* | *{}* |\n",
+            self.config.pause_label,
+            self.duration_str(&pause)
+        );
+        table
+    }
+
+    /// `H:MM`, or `H:MM:SS` with the `seconds` setting; see `format_duration_for`.
+    fn duration_str(&self, d: &Duration) -> String {
+        format_duration_for(d, &self.config)
+    }
+
+    pub fn sum_as_str(&self) -> String {
+        self.duration_str(&self.working_total())
+    }
+
+    /// The tasks, plus the paid breaks with `paid_pauses_count_as_work`.
+    /// A past day ends at its last entry; see `summary_end`.
+    pub fn working_total(&self) -> Duration {
+        self.working_total_at(&self.summary_end())
+    }
+
+    fn working_total_at(&self, now: &TimePoint) -> Duration {
+        let tasks = self
+            .grouped_times_at(now)
+            .into_iter()
+            .filter(|(text, _)| *text != self.config.pause_label)
+            .fold(Duration::ZERO, |total, (_, d)| total + d);
+        if self.config.paid_pauses_count_as_work {
+            tasks + self.paid_pause_total_at(now)
+        } else {
+            tasks
+        }
+    }
+
+    /// The daily target minus the working time; negative once in overtime.
+    pub fn remaining(&self) -> Duration {
+        Duration::minutes(self.config.daily_target) - self.working_total()
+    }
+
+    /// "1:20 left" or "+0:15 overtime", or nothing if there is no daily target.
+    pub fn target_status(&self) -> Option<String> {
+        if self.config.daily_target == 0 {
+            return None;
+        }
+        let remaining = self.remaining();
+        Some(if remaining.is_negative() {
+            format!("+{} overtime", self.duration_str(&-remaining))
+        } else {
+            format!("{} left", self.duration_str(&remaining))
+        })
+    }
+
+    /// How much of the daily target is done, past 1.0 in overtime.
+    pub fn target_ratio(&self) -> Option<f64> {
+        if self.config.daily_target == 0 {
+            return None;
+        }
+        let target = Duration::minutes(self.config.daily_target);
+        Some(self.working_total().as_seconds_f64() / target.as_seconds_f64())
+    }
+
+    /// One tab-separated line for the daily log: the date, the working total, and the tasks.
+    pub fn daily_log_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}",
+            self.date,
+            self.sum_as_str(),
+            self.time_by_tasks().replace('\n', ", ")
+        )
+    }
+
+    /// The summary as `{"tasks": {<task>: <duration>}, "total": <duration>}`,
+    /// with every duration given as both `H:MM` and whole minutes.
+    pub fn summary_json(&self) -> serde_json::Value {
+        fn duration_json(d: &Duration) -> serde_json::Value {
+            serde_json::json!({
+                "duration": format_duration(d),
+                "minutes": d.whole_minutes(),
+            })
+        }
+        let tasks: serde_json::Map<String, serde_json::Value> = self
+            .grouped_times()
+            .iter()
+            .map(|(text, duration)| (text.clone(), duration_json(duration)))
+            .collect();
+        serde_json::json!({
+            "tasks": tasks,
+            "total": duration_json(&self.working_total()),
+        })
+    }
+
+    /// `pause: H:MM`, followed by the paid and unpaid shares once there were paid breaks.
+    pub fn pause_time(&self) -> String {
+        let total = self.pause_total();
+        let paid = self.paid_pause_total();
+        let split = if paid.is_zero() {
+            String::new()
+        } else {
+            format!(
+                " (paid: {}, unpaid: {})",
+                self.duration_str(&paid),
+                self.duration_str(&(total - paid))
+            )
+        };
+        format!(
+            "{}: {}{}",
+            self.config.pause_label,
+            self.duration_str(&total),
+            split
+        )
+    }
+
+    /// Time spent in pauses, paid ones included.
+    pub fn pause_total(&self) -> Duration {
+        self.pause_total_at(&self.summary_end())
+    }
+
+    /// Time spent in breaks listed in `paid_pause_texts`.
+    pub fn paid_pause_total(&self) -> Duration {
+        self.paid_pause_total_at(&self.summary_end())
+    }
+
+    fn paid_pause_total_at(&self, now: &TimePoint) -> Duration {
+        self.windows_where(now, |tp| {
+            !is_comment(&tp.text) && self.config.is_paid_pause(&ungrouped_text(tp.text.clone()))
+        })
+        .into_iter()
+        .fold(Duration::ZERO, |total, (_, d)| total + d)
+    }
+
+    fn pause_total_at(&self, now: &TimePoint) -> Duration {
+        self.grouped_times_at(now)
+            .get(&self.config.pause_label)
+            .copied()
+            .unwrap_or(Duration::ZERO)
+    }
+}
+
+/// Splits one CSV line into fields, undoing the quoting `csv_field` applies.
+fn csv_record(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted field".to_string());
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r', '[', ']']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Escapes an iCalendar TEXT value.
+fn ics_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// A content line with its CRLF, folded so no line is longer than 75 bytes.
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for chr in line.chars() {
+        if width + chr.len_utf8() > 75 {
+            folded += "\r\n ";
+            width = 1;
+        }
+        folded.push(chr);
+        width += chr.len_utf8();
+    }
+    folded + "\r\n"
+}
+
+/// The `task: H:MM` lines of several sheets merged into one, pause left out, then the sum.
+pub fn combined_summary(sheets: &[TimeSheet], config: &Config) -> String {
+    let merged = sheets.iter().fold(
+        collections::BTreeMap::<String, Duration>::new(),
+        |mut merged, sheet| {
+            for (text, duration) in sheet.task_durations() {
+                *merged.entry(text).or_insert(Duration::ZERO) += duration;
+            }
+            merged
+        },
+    );
+    let format = |d: &Duration| format_duration_for(d, config);
+    let total = merged.values().fold(Duration::ZERO, |total, d| total + *d);
+    in_task_order(merged, config)
+        .iter()
+        .map(|(text, duration)| format!("{}: {}\n", text, format(duration)))
+        .chain(std::iter::once(format!("Sum: {}", format(&total))))
+        .collect()
+}
+
+/// Alphabetical tasks with the ones from `task_order` moved to the front, in that order.
+fn in_task_order(
+    tasks: impl IntoIterator<Item = (String, Duration)>,
+    config: &Config,
+) -> Vec<(String, Duration)> {
+    let order = &config.task_order;
+    tasks
+        .into_iter()
+        // The sort is stable, which keeps the alphabetical tail that way.
+        .sorted_by_key(|(text, _)| {
+            order
+                .iter()
+                .position(|task| task == text)
+                .unwrap_or(order.len())
+        })
+        .collect()
+}
+
+/// A duration as the `seconds` and `padded_durations` settings ask for.
+fn format_duration_for(d: &Duration, config: &Config) -> String {
+    match (config.seconds, config.padded_durations) {
+        (true, true) => pad_hours(format_duration_with_seconds(d)),
+        (true, false) => format_duration_with_seconds(d),
+        (false, true) => format_duration_padded(d),
+        (false, false) => format_duration(d),
+    }
+}
+
+/// Like `format_duration`, but with at least two digits of hours (`03:05`), so columns align.
+fn format_duration_padded(d: &Duration) -> String {
+    pad_hours(format_duration(d))
+}
+
+fn pad_hours(formatted: String) -> String {
+    let (sign, rest) = match formatted.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", formatted.as_str()),
+    };
+    match rest.find(':') {
+        Some(1) => format!("{}0{}", sign, rest),
+        _ => formatted,
+    }
+}
+
+/// A negative duration, e.g. over midnight or in overtime, gets a leading `-`.
+pub fn format_duration(d: &Duration) -> String {
+    let sign = if d.is_negative() { "-" } else { "" };
+    let d = d.abs();
+    format!("{}{}:{:02}", sign, d.whole_hours(), d.whole_minutes() % 60)
+}
+
+fn format_duration_with_seconds(d: &Duration) -> String {
+    let sign = if d.is_negative() { "-" } else { "" };
+    let d = d.abs();
+    format!(
+        "{}{}:{:02}:{:02}",
+        sign,
+        d.whole_hours(),
+        d.whole_minutes() % 60,
+        d.whole_seconds() % 60
+    )
+}
+
+fn format_minutes(minutes: i64) -> String {
+    let hours = minutes.div_euclid(60);
+    let minutes = minutes.rem_euclid(60);
+    format!("{:02}:{:02}", hours, minutes)
+}
+
+/**
+ * Render a time of day with `%H` (hours, past 24 after midnight), `%I` (12-hour hours),
+ * `%M` (minutes), `%S` (seconds), `%p` (AM/PM) and `%%`. Anything else is copied verbatim.
+ */
+pub fn format_time_of_day(minutes: i64, format: &str) -> Result<String, String> {
+    format_clock(minutes, 0, format)
+}
+
+fn format_clock(minutes: i64, seconds: i64, format: &str) -> Result<String, String> {
+    let hours = minutes.div_euclid(60);
+    let mut out = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('H') => out += &format!("{:02}", hours),
+            Some('I') => out += &format!("{:02}", (hours + 11).rem_euclid(12) + 1),
+            Some('M') => out += &format!("{:02}", minutes.rem_euclid(60)),
+            Some('S') => out += &format!("{:02}", seconds),
+            Some('p') => {
+                out += if hours.rem_euclid(24) < 12 {
+                    "AM"
+                } else {
+                    "PM"
+                }
+            }
+            Some('%') => out.push('%'),
+            Some(other) => return Err(format!("unknown time format directive %{}", other)),
+            None => return Err("time format ends with a lone %".to_string()),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        combined_summary, effective_text, fold_ics_line, format_duration, format_duration_padded,
+        format_duration_with_seconds, format_time_of_day, fuzzy_score, is_empty_input,
+        merge_daily_log, parse_minutes, parse_time_input, set_timezone, temp_path_for,
+        StoredTimePoint, TimePoint, TimeSheet, NOW_MARKER,
+    };
+    use crate::config::{Config, Timezone};
+    use std::{path::PathBuf, rc::Rc};
+    use time::{macros::date, Date, Duration, UtcOffset};
+
+    fn sheet(times: &[(&str, i64)]) -> TimeSheet {
+        TimeSheet {
+            date: date!(2026 - 01 - 05),
+            path: PathBuf::new(),
+            times: times
+                .iter()
+                .map(|(text, time)| TimePoint::new(text, *time))
+                .collect(),
+            selected: 0,
+            register: None,
+            config: Rc::new(Config::default()),
+            reordered_on_load: false,
+        }
+    }
+
+    /// The end point summaries run up to.
+    fn at(minutes: i64) -> TimePoint {
+        TimePoint::new("end", minutes)
+    }
+
+    fn stored_times(json: &str, date: Date) -> Vec<TimePoint> {
+        let stored: Vec<StoredTimePoint> = serde_json::from_str(json).unwrap();
+        stored
+            .into_iter()
+            .map(|stored| TimePoint::from_stored(stored, date))
+            .collect()
+    }
+
+    #[test]
+    fn stores_timestamps_and_still_reads_older_formats() {
+        let mut times = sheet(&[]);
+        times.date = date!(2026 - 03 - 09);
+        times.times = vec![
+            TimePoint::new("call", 9 * 60).with_seconds(15),
+            TimePoint::new("night shift", 24 * 60 + 30),
+        ];
+        let json = times.to_json().unwrap();
+        assert!(json.contains(r#""time":"2026-03-09T09:00:15"#), "{}", json);
+        assert!(json.contains(r#""time":"2026-03-10T00:30:00"#), "{}", json);
+        assert_eq!(stored_times(&json, times.date), times.times);
+
+        let legacy = r#"[
+            {"text": "a", "time": 540},
+            {"text": "b", "time": "9:30"},
+            {"text": "c", "time": [10, 0, 5, 0]},
+            {"text": "d", "time": "2026-03-09T11:15:00+01:00"},
+            {"text": "e", "time": "2026-03-09T12:00:00"}
+        ]"#;
+        let minutes: Vec<_> = stored_times(legacy, times.date)
+            .iter()
+            .map(|tp| (tp.time(), tp.seconds()))
+            .collect();
+        assert_eq!(minutes, [(540, 0), (570, 0), (600, 5), (675, 0), (720, 0)]);
+    }
+
+    #[test]
+    fn exports_work_blocks_as_calendar_events() {
+        set_timezone(Timezone::Fixed(UtcOffset::UTC));
+        let times = sheet(&[
+            ("call, then mail", 9 * 60),
+            ("lunch", 12 * 60),
+            ("code", 13 * 60),
+        ]);
+        let stamp = date!(2026 - 01 - 05).midnight().assume_utc();
+        let ics = times.ics_at(&at(14 * 60 + 30), stamp);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains(
+            "DTSTART:20260105T090000Z\r\nDTEND:20260105T120000Z\r\nSUMMARY:call\\, then mail\r\n"
+        ));
+        assert!(ics.contains("DTSTART:20260105T130000Z\r\nDTEND:20260105T143000Z\r\n"));
+        assert!(!ics.contains("lunch"));
+        assert!(fold_ics_line(&"x".repeat(100))
+            .split("\r\n")
+            .all(|line| line.len() <= 75));
+    }
+
+    fn effective(text: &str) -> String {
+        effective_text(text.to_string(), &Config::default())
+    }
+
+    #[test]
+    fn keeps_seconds_through_storage_and_durations() {
+        let stored =
+            r#"[{"text": "call", "time": [9, 0, 15, 0]}, {"text": "mail", "time": "9:01"}]"#;
+        let mut times = sheet(&[]);
+        times.times = stored_times(stored, times.date);
+        assert_eq!(
+            times.times[0],
+            TimePoint::new("call", 9 * 60).with_seconds(15)
+        );
+        let grouped = times.grouped_times_at(&at(9 * 60 + 1));
+        assert_eq!(grouped["call"], Duration::seconds(45));
+        assert_eq!(format_duration_with_seconds(&grouped["call"]), "0:00:45");
+
+        times.config = Rc::new(Config {
+            seconds: true,
+            ..Config::default()
+        });
+        assert_eq!(times.duration_str(&Duration::seconds(3725)), "1:02:05");
+    }
+
+    #[test]
+    fn formats_times_of_day() {
+        assert_eq!(format_time_of_day(9 * 60 + 5, "%H:%M").unwrap(), "09:05");
+        assert_eq!(format_time_of_day(0, "%I:%M %p").unwrap(), "12:00 AM");
+        assert_eq!(
+            format_time_of_day(12 * 60 + 30, "%I:%M %p").unwrap(),
+            "12:30 PM"
+        );
+        assert_eq!(format_time_of_day(25 * 60, "%H:%M").unwrap(), "25:00");
+        assert_eq!(format_time_of_day(13 * 60, "%Ih %%").unwrap(), "01h %");
+        assert!(format_time_of_day(0, "%H:%Q").is_err());
+        assert!(format_time_of_day(0, "%H%").is_err());
+    }
+
+    #[test]
+    fn shifts_following_entries_without_rounding() {
+        let mut times = sheet(&[("a", 8 * 60), ("b", 9 * 60 + 3), ("c", 10 * 60 + 7)]);
+        times.selected = 1;
+        times.shift_following(20);
+        let shifted: Vec<_> = times.times.iter().map(|tp| tp.time).collect();
+        assert_eq!(shifted, vec![8 * 60, 9 * 60 + 23, 10 * 60 + 27]);
+
+        times.shift_following(-90);
+        let shifted: Vec<_> = times
+            .times
+            .iter()
+            .map(|tp| (tp.text.as_str(), tp.time))
+            .collect();
+        assert_eq!(
+            shifted,
+            vec![("b", 7 * 60 + 53), ("a", 8 * 60), ("c", 8 * 60 + 57)]
+        );
+        assert_eq!(times.selected_text().as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn renames_by_raw_or_effective_text() {
+        let mut times = sheet(&[
+            ("meetng", 8 * 60),
+            ("[meetng] standup", 9 * 60),
+            ("coding", 10 * 60),
+            ("meetng", 11 * 60),
+        ]);
+        assert_eq!(times.rename_all("meetng", "meeting", false), 2);
+        assert_eq!(times.times[1].text, "[meetng] standup");
+        assert_eq!(times.rename_all("meetng", "meeting", true), 1);
+        let texts: Vec<_> = times.times.iter().map(|tp| tp.text.as_str()).collect();
+        assert_eq!(texts, vec!["meeting", "meeting", "coding", "meeting"]);
+    }
+
+    #[test]
+    fn splits_only_inside_the_selected_block() {
+        let mut times = sheet(&[("coding", 8 * 60), ("lunch", 12 * 60)]);
+        assert!(times.can_split_at(10 * 60));
+        assert!(!times.can_split_at(8 * 60));
+        assert!(!times.can_split_at(12 * 60));
+        times.selection_last();
+        assert!(times.can_split_at(13 * 60));
+        assert!(!times.can_split_at(11 * 60));
+        assert!(!sheet(&[]).can_split_at(10 * 60));
+    }
+
+    #[test]
+    fn merges_following_entries_with_the_same_effective_text() {
+        let mut times = sheet(&[
+            ("coding", 8 * 60),
+            ("[coding] notes", 9 * 60),
+            ("coding #review", 10 * 60),
+            ("lunch", 12 * 60),
+            ("coding", 13 * 60),
+        ]);
+        assert_eq!(times.mergeable_count(), 2);
+        times.merge_following();
+        let kept: Vec<_> = times
+            .times
+            .iter()
+            .map(|tp| (tp.text.as_str(), tp.time))
+            .collect();
+        assert_eq!(
+            kept,
+            vec![("coding", 8 * 60), ("lunch", 12 * 60), ("coding", 13 * 60)]
+        );
+        assert_eq!(times.mergeable_count(), 0);
+    }
+
+    #[test]
+    fn pages_through_the_selection_within_bounds() {
+        let points: Vec<(String, i64)> = (0..10).map(|i| (format!("t{}", i), i * 60)).collect();
+        let points: Vec<(&str, i64)> = points.iter().map(|(t, m)| (t.as_str(), *m)).collect();
+        let mut times = sheet(&points);
+        times.selection_first();
+        times.selection_by(4);
+        assert_eq!(times.selected_index(), Some(4));
+        times.selection_by(8);
+        assert_eq!(times.selected_index(), Some(9));
+        times.selection_by(-3);
+        assert_eq!(times.selected_index(), Some(6));
+        times.selection_by(-8);
+        assert_eq!(times.selected_index(), Some(0));
+    }
+
+    #[test]
+    fn jumps_to_first_and_last_entry() {
+        let mut times = sheet(&[("a", 8 * 60), ("b", 9 * 60), ("c", 10 * 60)]);
+        times.selection_last();
+        assert_eq!(times.selected_index(), Some(2));
+        times.selection_first();
+        assert_eq!(times.selected_index(), Some(0));
+
+        let mut empty = sheet(&[]);
+        empty.selection_last();
+        empty.selection_first();
+        assert_eq!(empty.selected_index(), None);
+    }
+
+    #[test]
+    fn current_task_skips_future_entries_and_pauses() {
+        let times = sheet(&[
+            ("coding #work", 8 * 60),
+            ("lunch", 12 * 60),
+            ("end", 17 * 60),
+        ]);
+        assert_eq!(times.current_task_at(&at(7 * 60)), None);
+        assert_eq!(
+            times.current_task_at(&at(9 * 60)).as_deref(),
+            Some("coding")
+        );
+        assert_eq!(times.current_task_at(&at(12 * 60)), None);
+        assert_eq!(times.current_task_at(&at(18 * 60)), None);
+    }
+
+    #[test]
+    fn completes_from_raw_and_override_texts() {
+        let times = sheet(&[
+            ("code review [review]", 8 * 60),
+            ("Reading", 9 * 60),
+            ("code review [review]", 10 * 60),
+            ("mail", 11 * 60),
+        ]);
+        assert_eq!(
+            times.completions("re"),
+            vec!["code review [review]", "Reading"]
+        );
+        assert_eq!(times.completions("code"), vec!["code review [review]"]);
+        assert!(times.completions("x").is_empty());
+    }
+
+    #[test]
+    fn keeps_notes_out_of_the_grouping() {
+        let mut times = sheet(&[("mail", 10 * 60)]);
+        times
+            .times
+            .insert(0, TimePoint::from_input("bugfix // the null deref", 9 * 60));
+        times
+            .times
+            .insert(1, TimePoint::from_input("bugfix // tests", 9 * 60 + 30));
+        assert_eq!(
+            times.grouped_times_at(&at(10 * 60))["bugfix"],
+            Duration::hours(1)
+        );
+        assert_eq!(
+            times.times[0].format_with("%H:%M"),
+            "[09:00] bugfix — the null deref"
+        );
+        assert_eq!(times.times[0].input_text(), "bugfix // the null deref");
+        assert_eq!(
+            TimePoint::from_input("bugfix //  ", 0),
+            TimePoint::new("bugfix", 0)
+        );
+        assert_eq!(TimePoint::from_input(" // note", 0), TimePoint::new("", 0));
+        assert!(is_empty_input(" // note"));
+        assert!(is_empty_input("   "));
+        assert!(!is_empty_input("bugfix // note"));
+
+        let json = times.to_json().unwrap();
+        assert!(json.contains(r#""note":"tests""#), "{}", json);
+        assert!(!json.contains(r#""note":null"#), "{}", json);
+        assert_eq!(stored_times(&json, times.date), times.times);
+    }
+
+    #[test]
+    fn warns_about_entries_at_the_same_time() {
+        let times = sheet(&[("a", 60), ("b", 60), ("c", 60), ("d", 90)]);
+        assert_eq!(times.duplicate_times(), 2);
+        assert_eq!(
+            times.warnings(),
+            vec!["2 entries start with the one before them"]
+        );
+        assert!(sheet(&[("a", 60), ("b", 61)]).warnings().is_empty());
+    }
+
+    #[test]
+    fn finds_the_last_task_before_a_break() {
+        let times = sheet(&[
+            ("code review [review]", 8 * 60),
+            ("lunch", 12 * 60),
+            ("end", 13 * 60),
+        ]);
+        assert_eq!(times.last_task_text(), Some("code review [review]"));
+        assert_eq!(sheet(&[("pause", 8 * 60)]).last_task_text(), None);
+    }
+
+    #[test]
+    fn lists_the_duration_of_each_entry() {
+        let times = sheet(&[("standup", 9 * 60), ("code", 9 * 60 + 15)]);
+        assert_eq!(
+            times.entry_durations_at(&at(10 * 60)),
+            vec![Some(Duration::minutes(15)), Some(Duration::minutes(45))]
+        );
+        let ended = sheet(&[("code", 9 * 60), ("end", 10 * 60)]);
+        assert_eq!(
+            ended.entry_durations_at(&at(11 * 60)),
+            vec![Some(Duration::hours(1)), None]
+        );
+    }
+
+    #[test]
+    fn totals_the_pauses_separately() {
+        let times = sheet(&[
+            ("work", 8 * 60),
+            ("lunch", 12 * 60),
+            ("work", 12 * 60 + 30),
+            ("break", 15 * 60),
+            ("work", 15 * 60 + 15),
+        ]);
+        assert_eq!(times.pause_total_at(&at(17 * 60)), Duration::minutes(45));
+        assert_eq!(
+            sheet(&[("work", 8 * 60)]).pause_total_at(&at(9 * 60)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn knows_when_a_break_is_running() {
+        let times = sheet(&[("work", 8 * 60), ("lunch", 12 * 60), ("work", 13 * 60)]);
+        assert!(!times.on_break_at(&at(7 * 60)));
+        assert!(!times.on_break_at(&at(9 * 60)));
+        assert!(times.on_break_at(&at(12 * 60 + 30)));
+        assert!(!times.on_break_at(&at(14 * 60)));
+    }
+
+    #[test]
+    fn measures_progress_towards_the_target() {
+        let mut times = sheet(&[("work", 8 * 60), ("end", 12 * 60)]);
+        assert_eq!(times.target_ratio(), Some(0.5));
+        times.config = Rc::new(Config {
+            daily_target: 0,
+            ..Config::default()
+        });
+        assert_eq!(times.target_ratio(), None);
+    }
+
+    #[test]
+    fn comments_are_left_out_of_the_windows() {
+        let times = sheet(&[
+            ("code", 9 * 60),
+            ("--- afternoon ---", 12 * 60),
+            ("code", 13 * 60),
+            ("// wrap up", 17 * 60),
+        ]);
+        let now = at(18 * 60);
+        assert_eq!(times.grouped_times_at(&now)["code"], Duration::hours(9));
+        assert_eq!(times.grouped_times_at(&now).len(), 1);
+        assert_eq!(times.current_task_at(&now).as_deref(), Some("code"));
+        assert_eq!(
+            times.entry_durations_at(&now),
+            vec![
+                Some(Duration::hours(4)),
+                None,
+                Some(Duration::hours(5)),
+                None
+            ]
+        );
+        assert_eq!(effective("--- [not a group] ---"), "--- [not a group] ---");
+    }
+
+    #[test]
+    fn jumps_between_search_matches() {
+        let mut times = sheet(&[
+            ("Email triage", 8 * 60),
+            ("code", 9 * 60),
+            ("reply [email]", 10 * 60),
+            ("lunch", 12 * 60),
+        ]);
+        assert_eq!(times.matching("EMAIL"), vec![0, 2]);
+        times.select_match("email", true);
+        assert_eq!(times.selected, 2);
+        times.select_match("email", true);
+        assert_eq!(times.selected, 0);
+        times.select_match("email", false);
+        assert_eq!(times.selected, 2);
+        times.select_match("nothing", true);
+        assert_eq!(times.selected, 2);
+    }
+
+    #[test]
+    fn ranks_fuzzy_search_matches() {
+        let mut times = sheet(&[
+            ("crave snacks", 8 * 60),
+            ("calendar review", 9 * 60),
+            ("code review", 10 * 60),
+            ("lunch", 12 * 60),
+        ]);
+        times.config = Rc::new(Config {
+            fuzzy_search: true,
+            ..Config::default()
+        });
+        // The substring beats the scattered matches, which rank by their gaps.
+        assert_eq!(times.matching("crv"), vec![2, 0, 1]);
+        assert_eq!(times.matching("cr"), vec![0, 2, 1]);
+        assert_eq!(times.matching("rev"), vec![2, 1]);
+        times.select_best_match("crv");
+        assert_eq!(times.selected, 2);
+        times.select_match("crv", true);
+        assert_eq!(times.selected, 0);
+        times.select_match("crv", true);
+        assert_eq!(times.selected, 1);
+        times.select_match("crv", true);
+        assert_eq!(times.selected, 2);
+        times.select_match("crv", false);
+        assert_eq!(times.selected, 1);
+        assert!(times.matching("xyz").is_empty());
+        assert!(times.matching(" ").is_empty());
+        assert!(times.matching("").is_empty());
+        assert_eq!(fuzzy_score("aéb", "ab"), fuzzy_score("axb", "ab"));
+        assert_eq!(fuzzy_score("é code", "code"), fuzzy_score("e code", "code"));
+    }
+
+    #[test]
+    fn rounds_every_entry_to_the_nearest_step() {
+        let mut times = sheet(&[
+            ("a", 9 * 60 + 2),
+            ("b", 9 * 60 + 3),
+            ("c", 9 * 60 + 4),
+            ("d", 10 * 60 + 7),
+            ("e", 10 * 60 + 7),
+        ]);
+        times.times[0] = times.times[0].clone().with_seconds(40);
+        times.round_all(5);
+        let minutes: Vec<_> = times.times.iter().map(TimePoint::time).collect();
+        assert_eq!(
+            minutes,
+            [
+                9 * 60 + 5,
+                9 * 60 + 10,
+                9 * 60 + 15,
+                10 * 60 + 5,
+                10 * 60 + 5
+            ]
+        );
+        assert_eq!(times.times[0].seconds(), 0);
+    }
+
+    #[test]
+    fn past_days_stop_at_their_last_entry() {
+        let unended = sheet(&[("mail", 8 * 60), ("code", 9 * 60)]);
+        assert_eq!(unended.working_total(), Duration::hours(1));
+        assert_eq!(unended.sum_as_str(), "1:00");
+    }
+
+    #[test]
+    fn a_tagged_pause_at_the_end_stops_the_day() {
+        let times = sheet(&[("code", 8 * 60), ("lunch #personal", 9 * 60)]);
+        assert_eq!(times.working_total_at(&at(14 * 60)), Duration::hours(1));
+        assert_eq!(times.long_running_at(&at(14 * 60)), None);
+        let times = sheet(&[("code", 8 * 60), ("x [pause]", 9 * 60)]);
+        assert_eq!(times.long_running_at(&at(14 * 60)), None);
+    }
+
+    #[test]
+    fn merges_the_tasks_of_several_days() {
+        let monday = sheet(&[("code", 8 * 60), ("lunch", 12 * 60), ("end", 12 * 60 + 30)]);
+        let tuesday = sheet(&[("mail", 8 * 60), ("code", 9 * 60), ("end", 11 * 60)]);
+        let config = Config::default();
+        assert_eq!(
+            combined_summary(&[monday, tuesday], &config),
+            "code: 6:00\nmail: 1:00\nSum: 7:00"
+        );
+        assert_eq!(combined_summary(&[], &config), "Sum: 0:00");
+        // A past day without `end` stops at its last entry instead of running until now.
+        let unended = sheet(&[("mail", 8 * 60), ("code", 9 * 60)]);
+        assert_eq!(
+            combined_summary(&[unended], &config),
+            "mail: 1:00\nSum: 1:00"
+        );
+    }
+
+    #[test]
+    fn summarizes_as_plain_text() {
+        let times = sheet(&[("code", 8 * 60), ("mail", 9 * 60), ("end", 9 * 60 + 30)]);
+        assert_eq!(times.plain_summary(), "code: 1:00\nmail: 0:30\nSum: 1:30");
+        assert_eq!(sheet(&[]).plain_summary(), "Sum: 0:00");
+    }
+
+    #[test]
+    fn duplicates_the_selected_entry_now() {
+        let mut times = sheet(&[("call [acme]", 0), ("mail", 1)]);
+        times.selected = 1;
+        times.yank();
+        times.selected = 0;
+        times.duplicate_selected(5);
+        let copy = &times.times[times.selected];
+        assert_eq!(copy.text, "call [acme]");
+        assert_eq!(copy.time() % 5, 0);
+        assert_eq!(copy.seconds(), 0);
+        assert_eq!(times.times.len(), 3);
+        assert_eq!(times.register.as_ref().unwrap().text, "mail");
+    }
+
+    #[test]
+    fn removes_and_shifts_a_range_of_entries() {
+        let mut times = sheet(&[("a", 60), ("b", 120), ("c", 180), ("d", 240)]);
+        times.selected = 2;
+        times.shift_range(1, 2, 90);
+        let order: Vec<_> = times.times.iter().map(|tp| tp.text.as_str()).collect();
+        assert_eq!(order, ["a", "b", "d", "c"]);
+        assert_eq!(times.times[times.selected].text, "c");
+        times.shift_range(0, 0, -120);
+        assert_eq!(times.times[0].time(), 0);
+
+        times.remove_range(1, 2);
+        let order: Vec<_> = times.times.iter().map(|tp| tp.text.as_str()).collect();
+        assert_eq!(order, ["a", "c"]);
+        assert_eq!(times.selected, 1);
+        times.remove_range(0, 5);
+        assert!(times.times.is_empty());
+        assert_eq!(times.selected, 0);
+    }
+
+    #[test]
+    fn lists_task_durations_without_pause() {
+        let times = sheet(&[
+            ("mail", 8 * 60),
+            ("lunch", 9 * 60),
+            ("code", 10 * 60),
+            ("end", 11 * 60),
+        ]);
+        assert_eq!(
+            times.task_durations(),
+            [
+                ("code".to_string(), Duration::hours(1)),
+                ("mail".to_string(), Duration::hours(1)),
+            ]
+        );
+        assert_eq!(times.pause_total(), Duration::hours(1));
+    }
+
+    #[test]
+    fn lists_pinned_tasks_first() {
+        let mut times = sheet(&[
+            ("admin", 8 * 60),
+            ("code", 9 * 60),
+            ("mail", 10 * 60),
+            ("review", 11 * 60),
+            ("end", 12 * 60),
+        ]);
+        times.config = Rc::new(Config {
+            task_order: vec![
+                "review".to_string(),
+                "code".to_string(),
+                "missing".to_string(),
+            ],
+            ..Config::default()
+        });
+        assert_eq!(
+            times.time_by_tasks(),
+            "review: 1:00\ncode: 1:00\nadmin: 1:00\nmail: 1:00"
+        );
+    }
+
+    #[test]
+    fn knows_which_times_are_in_the_future() {
+        let today = TimeSheet {
+            date: TimeSheet::current_date(),
+            ..sheet(&[])
+        };
+        let now = today.now_point("").time();
+        assert!(!today.is_in_future(now));
+        assert!(today.is_in_future(now + 5));
+        // The day of `sheet` is long over, so none of it lies in the future.
+        assert!(!sheet(&[]).is_in_future(47 * 60));
+    }
+
+    #[test]
+    fn splits_the_work_into_hours() {
+        let times = sheet(&[
+            ("code", 9 * 60 + 30),
+            ("lunch #team", 11 * 60 + 15),
+            ("code", 12 * 60),
+        ]);
+        let hours = times.minutes_by_hour_at(&at(12 * 60 + 20));
+        assert_eq!(hours[9], Duration::minutes(30));
+        assert_eq!(hours[10], Duration::minutes(60));
+        assert_eq!(hours[11], Duration::minutes(15));
+        assert_eq!(hours[12], Duration::minutes(20));
+        assert_eq!(hours.iter().filter(|d| !d.is_zero()).count(), 4);
+    }
+
+    #[test]
+    fn exports_one_json_object_per_entry() {
+        set_timezone(Timezone::Fixed(UtcOffset::UTC));
+        let mut times = sheet(&[
+            ("call [acme]", 9 * 60),
+            ("-- note", 9 * 60 + 10),
+            ("end", 10 * 60),
+        ]);
+        times.times[0].note = Some("kickoff".to_string());
+        let lines: Vec<serde_json::Value> = times
+            .to_jsonl_at(&at(11 * 60))
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            serde_json::json!({
+                "time": "2026-01-05T09:00:00Z",
+                "text": "call [acme]",
+                "effective_text": "acme",
+                "duration": "1:00",
+                "seconds": 3600,
+                "note": "kickoff",
+            })
+        );
+        assert_eq!(lines[1]["duration"], serde_json::Value::Null);
+        assert_eq!(lines[2]["effective_text"], "pause");
+        assert_eq!(lines[2]["seconds"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn leaves_non_billable_work_out_of_the_billable_total() {
+        let mut times = sheet(&[("code", 8 * 60), ("standup", 9 * 60), ("code", 9 * 60 + 20)]);
+        assert_eq!(times.billable_total_str(), None);
+        times.selected = 1;
+        times.toggle_selected_billable();
+        assert_eq!(times.non_billable(), [1]);
+        let now = at(10 * 60);
+        let billable: Duration = times
+            .billable_task_durations_at(&now)
+            .into_iter()
+            .map(|(_, d)| d)
+            .sum();
+        assert_eq!(billable, Duration::minutes(100));
+        assert!(times.grouped_times_at(&now).contains_key("standup"));
+
+        set_timezone(Timezone::Fixed(UtcOffset::UTC));
+        let stored: Vec<_> = times
+            .times
+            .iter()
+            .map(|tp| serde_json::to_value(tp.to_stored(times.date)).unwrap())
+            .collect();
+        assert_eq!(stored[1]["billable"], false);
+        assert!(stored[0].get("billable").is_none());
+        let read = stored_times(
+            r#"[{"text": "a", "time": "9:00"}, {"text": "b", "time": "10:00", "billable": false}]"#,
+            times.date,
+        );
+        assert!(read[0].billable);
+        assert!(!read[1].billable);
+    }
+
+    #[test]
+    fn splits_the_pause_into_paid_and_unpaid() {
+        let config = Config {
+            paid_pause_texts: vec!["coffee".to_string()],
+            ..Config::default()
+        };
+        let times = TimeSheet {
+            config: Rc::new(config.clone()),
+            ..sheet(&[
+                ("code", 8 * 60),
+                ("Coffee #kitchen", 10 * 60),
+                ("code", 10 * 60 + 15),
+                ("lunch", 12 * 60),
+                ("code", 12 * 60 + 45),
+                ("end", 14 * 60),
+            ])
+        };
+        assert_eq!(times.time_by_tasks(), "code: 5:00");
+        assert_eq!(times.sum_as_str(), "5:00");
+        assert_eq!(times.pause_time(), "pause: 1:00 (paid: 0:15, unpaid: 0:45)");
+        let times = TimeSheet {
+            config: Rc::new(Config {
+                paid_pauses_count_as_work: true,
+                ..config
+            }),
+            ..times
+        };
+        assert_eq!(times.sum_as_str(), "5:15");
+        assert_eq!(times.time_by_tasks(), "code: 5:00");
+    }
+
+    #[test]
+    fn flags_a_long_running_last_entry() {
+        let times = sheet(&[("call", 8 * 60), ("code", 9 * 60)]);
+        assert_eq!(times.long_running_at(&at(12 * 60)), None);
+        assert_eq!(
+            times.long_running_at(&at(12 * 60 + 1)),
+            Some((1, Duration::minutes(3 * 60 + 1)))
+        );
+        // The test sheet lies in the past, where nothing runs any more.
+        assert_eq!(times.long_running(), None);
+        let ended = sheet(&[("code", 9 * 60), ("end", 10 * 60), ("-- note", 10 * 60)]);
+        assert_eq!(ended.long_running_at(&at(18 * 60)), None);
+        let times = TimeSheet {
+            config: Rc::new(Config {
+                long_running_minutes: 0,
+                ..Config::default()
+            }),
+            ..times
+        };
+        assert_eq!(times.long_running_at(&at(18 * 60)), None);
+    }
+
+    #[test]
+    fn sums_up_the_time_per_project() {
+        let mut times = sheet(&[
+            ("call", 8 * 60),
+            ("code", 9 * 60),
+            ("lunch", 12 * 60),
+            ("code", 13 * 60),
+            ("end", 14 * 60),
+        ]);
+        assert_eq!(times.time_by_project(), "");
+        times.selected = 0;
+        times.set_selected_project(" @acme ");
+        times.selected = 3;
+        times.set_selected_project("acme");
+        times.selected = 1;
+        times.set_selected_project("globex");
+        assert_eq!(times.times[0].format_with("%H:%M"), "[08:00] call @acme");
+        assert_eq!(times.time_by_project(), "@acme: 2:00\n@globex: 3:00");
+        times.set_selected_project("");
+        assert_eq!(times.selected_project(), None);
+        assert_eq!(times.time_by_project(), "@acme: 2:00\n(unassigned): 3:00");
+    }
+
+    #[test]
+    fn writes_one_line_for_the_daily_log() {
+        let times = sheet(&[("code", 8 * 60), ("mail", 9 * 60), ("end", 9 * 60 + 30)]);
+        assert_eq!(
+            times.daily_log_line(),
+            "2026-01-05\t1:30\tcode: 1:00, mail: 0:30"
+        );
+    }
+
+    #[test]
+    fn keeps_one_daily_log_line_per_day() {
+        let log = "2026-01-04\t8:00\tcode: 8:00\n2026-01-05\t1:00\tmail: 1:00\n";
+        let lines = [
+            "2026-01-05\t2:00\tmail: 2:00".to_string(),
+            "2026-01-06\t0:30\tcall: 0:30".to_string(),
+        ];
+        assert_eq!(
+            merge_daily_log(log, &lines),
+            "2026-01-04\t8:00\tcode: 8:00\n2026-01-05\t2:00\tmail: 2:00\n2026-01-06\t0:30\tcall: 0:30\n"
+        );
+        let twice = "2026-01-05\t1:00\tmail: 1:00\n2026-01-05\t1:30\tmail: 1:30\n";
+        assert_eq!(
+            merge_daily_log(twice, &lines[..1]),
+            "2026-01-05\t2:00\tmail: 2:00\n"
+        );
+        assert_eq!(merge_daily_log("", &[]), "");
+    }
+
+    #[test]
+    fn finds_the_entry_now_falls_into() {
+        let times = sheet(&[("code", 8 * 60), ("-- later", 9 * 60), ("call", 15 * 60)]);
+        assert_eq!(times.now_index_at(&at(7 * 60)), None);
+        assert_eq!(times.now_index_at(&at(8 * 60)), Some(0));
+        assert_eq!(times.now_index_at(&at(12 * 60)), Some(1));
+        assert_eq!(times.now_index_at(&at(16 * 60)), Some(2));
+        // The marker is only for today, and the test sheet lies in the past.
+        assert!(times
+            .printable_with_preview(None, false)
+            .iter()
+            .all(|(row, _)| !row.ends_with(NOW_MARKER.trim())));
+    }
+
+    #[test]
+    fn marks_pause_rows() {
+        let times = sheet(&[("work", 8 * 60), ("[lunch]", 12 * 60), ("end", 17 * 60)]);
+        let rows = times.printable_with_preview(Some((1, TimePoint::new("break", 10 * 60))), false);
+        let pauses: Vec<bool> = rows.iter().map(|(_, pause)| *pause).collect();
+        assert_eq!(pauses, vec![false, true, true, true]);
+    }
+
+    #[test]
+    fn window_across_midnight_is_positive() {
+        let sheet = sheet(&[("late task", 23 * 60 + 50), ("end", 24 * 60 + 10)]);
+        let grouped = sheet.grouped_times_at(&at(0));
+        assert_eq!(grouped["late task"], Duration::minutes(20));
+    }
+
+    #[test]
+    fn fills_in_the_status_line() {
+        let times = sheet(&[("mail", 8 * 60), ("[code] review", 9 * 60)]);
+        let now = at(10 * 60 + 20);
+        assert_eq!(
+            times.running_at(&now),
+            Some(("code".to_string(), Duration::minutes(80)))
+        );
+        assert_eq!(times.status_line_at(&now), "code 1:20 | total 2:20");
+        let ended = sheet(&[("mail", 8 * 60), ("end", 9 * 60)]);
+        assert_eq!(ended.running_at(&now), None);
+        assert_eq!(ended.status_line_at(&now), "idle | total 1:00");
+        let ended = TimeSheet {
+            config: Rc::new(Config {
+                pause_texts: vec!["lunch".to_string()],
+                ..Config::default()
+            }),
+            ..ended
+        };
+        assert_eq!(ended.running_at(&now), None);
+    }
+
+    #[test]
+    fn parses_times_relative_to_now_and_the_previous_entry() {
+        let now = 10 * 60;
+        assert_eq!(parse_time_input("now", now, None), Ok(600));
+        assert_eq!(parse_time_input("now-15", now, None), Ok(585));
+        assert_eq!(parse_time_input(" now + 5 ", now, None), Ok(605));
+        assert_eq!(parse_time_input("+20", now, Some(9 * 60)), Ok(560));
+        assert_eq!(parse_time_input("-10", now, Some(9 * 60)), Ok(530));
+        assert_eq!(parse_time_input("9:30", now, Some(9 * 60)), Ok(570));
+        assert!(parse_time_input("+20", now, None).is_err());
+        assert!(parse_time_input("now-1:30", now, None).is_err());
+        assert!(parse_time_input("now*2", now, None).is_err());
+        assert!(parse_time_input("now–15", now, None).is_err());
+        assert!(parse_time_input("nowé", now, None).is_err());
+        assert!(parse_time_input("-10", now, Some(5)).is_err());
+    }
+
+    #[test]
+    fn rejects_minutes_over_59_in_clock_form() {
+        assert!(parse_minutes("10:200").is_err());
+    }
+
+    #[test]
+    fn accepts_clock_form_with_valid_minutes() {
+        assert_eq!(parse_minutes("10:20").unwrap(), 620);
+    }
+
+    #[test]
+    fn rejects_compact_form_with_hour_at_48_or_more() {
+        assert!(parse_minutes("4820").is_err());
+    }
+
+    #[test]
+    fn rejects_compact_form_with_minutes_over_59() {
+        assert!(parse_minutes("1060").is_err());
+    }
+
+    #[test]
+    fn accepts_three_digit_compact_form() {
+        assert_eq!(parse_minutes("940").unwrap(), 580);
+    }
+
+    #[test]
+    fn formats_zero_duration() {
+        assert_eq!(format_duration(&Duration::ZERO), "0:00");
+    }
+
+    #[test]
+    fn formats_durations_around_the_hour() {
+        assert_eq!(format_duration(&Duration::minutes(59)), "0:59");
+        assert_eq!(format_duration(&Duration::minutes(60)), "1:00");
+        assert_eq!(format_duration(&Duration::minutes(125)), "2:05");
+    }
+
+    #[test]
+    fn pads_hours_to_two_digits() {
+        assert_eq!(format_duration_padded(&Duration::minutes(185)), "03:05");
+        assert_eq!(format_duration_padded(&Duration::ZERO), "00:00");
+        assert_eq!(format_duration_padded(&Duration::minutes(-15)), "-00:15");
+        assert_eq!(format_duration_padded(&Duration::hours(123)), "123:00");
+        let mut times = sheet(&[("code", 8 * 60), ("end", 9 * 60 + 5)]);
+        times.config = Rc::new(Config {
+            padded_durations: true,
+            ..Config::default()
+        });
+        assert_eq!(times.time_by_tasks(), "code: 01:05");
+        assert!(times.to_markdown().contains("| code | 01:05 |"));
+    }
+
+    #[test]
+    fn formats_negative_durations_with_a_sign() {
+        assert_eq!(format_duration(&Duration::minutes(-15)), "-0:15");
+        assert_eq!(format_duration(&-Duration::ZERO), "0:00");
+        assert_eq!(format_duration(&Duration::minutes(-125)), "-2:05");
+        assert_eq!(format_duration(&Duration::hours(-100)), "-100:00");
+        assert_eq!(
+            format_duration_with_seconds(&Duration::seconds(-3725)),
+            "-1:02:05"
+        );
+    }
+
+    #[test]
+    fn shift_rounds_the_shifted_time() {
+        let mut sheet = sheet(&[("task", 9 * 60 + 7)]);
+        sheet.shift_current(15, 15);
+        assert_eq!(sheet.times[0].time(), 9 * 60 + 15);
+        sheet.shift_current(-1, 15);
+        assert_eq!(sheet.times[0].time(), 9 * 60);
+    }
+
+    #[test]
+    fn shift_with_round_to_one_is_exact() {
+        let mut sheet = sheet(&[("task", 9 * 60 + 7)]);
+        sheet.shift_current(-1, 1);
+        assert_eq!(sheet.times[0].time(), 9 * 60 + 6);
+    }
+
+    #[test]
+    fn cut_then_paste_in_place_restores_the_entry() {
+        let mut sheet = sheet(&[("a", 60), ("b", 120), ("c", 180)]);
+        sheet.selected = 1;
+        let before = sheet.times.clone();
+        sheet.remove_current();
+        assert_eq!(sheet.register, Some(TimePoint::new("b", 120)));
+        sheet.paste_in_place();
+        assert_eq!(sheet.times, before);
+        assert_eq!(sheet.selected, 1);
+    }
+
+    #[test]
+    fn cutting_the_only_entry_leaves_an_empty_sheet() {
+        let mut sheet = sheet(&[("only", 60)]);
+        sheet.remove_current();
+        assert!(sheet.times.is_empty());
+        assert_eq!(sheet.selected_index(), None);
+        assert_eq!(sheet.register, Some(TimePoint::new("only", 60)));
+    }
+
+    #[test]
+    fn moving_takes_the_neighbours_time() {
+        let mut sheet = sheet(&[("a", 60), ("b", 120), ("c", 180)]);
+        sheet.selected = 2;
+        sheet.move_up();
+        assert_eq!(sheet.selected, 1);
+        assert_eq!(sheet.times[1], TimePoint::new("c", 120));
+        assert_eq!(sheet.times[2], TimePoint::new("b", 120));
+        sheet.selection_first();
+        sheet.move_up();
+        assert_eq!(sheet.selected, 0);
+        sheet.move_down();
+        assert_eq!(sheet.times[1], TimePoint::new("a", 120));
+    }
+
+    #[test]
+    fn csv_quotes_commas_quotes_and_brackets() {
+        let sheet = sheet(&[
+            ("plain", 9 * 60),
+            ("a, b", 10 * 60),
+            ("say \"hi\" [x]", 11 * 60),
+            ("end", 12 * 60),
+        ]);
+        let csv = sheet.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("time,text"));
+        assert_eq!(lines.next(), Some("09:00,plain"));
+        assert_eq!(lines.next(), Some("10:00,\"a, b\""));
+        assert_eq!(lines.next(), Some("11:00,\"say \"\"hi\"\" [x]\""));
+        let sheet = TimeSheet {
+            config: Rc::new(Config {
+                time_format: "%I:%M %p".to_string(),
+                ..Config::default()
+            }),
+            ..sheet
+        };
+        assert_eq!(sheet.to_csv().lines().nth(4), Some("12:00,end"));
+    }
+
+    #[test]
+    fn imports_what_the_csv_export_writes() {
+        let mut exported = sheet(&[
+            ("say \"hi\", [x]", 11 * 60),
+            ("plain", 9 * 60),
+            ("bugfix", 10 * 60),
+            ("end", 12 * 60),
+        ]);
+        exported.times[2].note = Some("the null deref".to_string());
+        let mut imported = sheet(&[("already there", 8 * 60)]);
+        let count = imported.import_csv(exported.to_csv().as_bytes()).unwrap();
+        assert_eq!(count, 4);
+        let texts: Vec<_> = imported.times.iter().map(|tp| tp.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec!["already there", "plain", "bugfix", "say \"hi\", [x]", "end"]
+        );
+        let mut expected = exported.times.clone();
+        expected.sort_by_key(TimePoint::total_seconds);
+        assert_eq!(imported.times[1..], expected[..]);
+
+        let exported = TimeSheet {
+            config: Rc::new(Config {
+                time_format: "%I:%M %p".to_string(),
+                seconds: true,
+                ..Config::default()
+            }),
+            times: vec![
+                TimePoint::new("late", 13 * 60).with_seconds(30),
+                TimePoint::new("end", 14 * 60),
+            ],
+            ..exported
+        };
+        let mut imported = sheet(&[]);
+        imported.import_csv(exported.to_csv().as_bytes()).unwrap();
+        assert_eq!(imported.times, exported.times);
+    }
+
+    #[test]
+    fn csv_import_reports_the_bad_line() {
+        let mut times = sheet(&[]);
+        let err = times
+            .import_csv("time,text\n09:00,ok\n25:99,bad\n".as_bytes())
+            .unwrap_err();
+        assert!(err.starts_with("line 3:"), "{}", err);
+        assert_eq!(
+            times.import_csv("09:00\n".as_bytes()).unwrap_err(),
+            "line 1: expected 2 fields, found 1"
+        );
+        assert!(times.times.is_empty());
+    }
+
+    #[test]
+    fn markdown_lists_tasks_total_and_pause() {
+        let sheet = sheet(&[
+            ("a|b", 9 * 60),
+            ("lunch", 10 * 60),
+            ("c", 10 * 60 + 30),
+            ("end", 11 * 60),
+        ]);
+        assert_eq!(
+            sheet.to_markdown(),
+            "| Task | Duration |\n\
+             | --- | ---: |\n\
+             | a\\|b | 1:00 |\n\
+             | c | 0:30 |\n\
+             | **Total** | **1:30** |\n\
+             | *pause* | *0:30* |\n"
+        );
+    }
+
+    #[test]
+    fn tags_are_stripped_from_the_effective_text() {
+        assert_eq!(effective("fix parser #projectx #bug"), "fix parser");
+        assert_eq!(effective("lunch #personal"), "pause");
+        assert_eq!(effective("notes [coding] #x"), "coding");
+        assert_eq!(effective("call with [clientA] about renewal"), "clientA");
+        assert_eq!(effective("[acme] call about [billing]"), "acme / billing");
+        assert_eq!(effective("a [nested [group]]"), "a [nested [group]]");
+        assert_eq!(effective("unbalanced ] [x"), "unbalanced ] [x");
+        assert_eq!(effective("empty []"), "empty []");
+        assert_eq!(effective("#standup"), "#standup");
+    }
+
+    #[test]
+    fn every_tag_gets_the_full_duration() {
+        let sheet = sheet(&[
+            ("fix parser #projectx #bug", 9 * 60),
+            ("review #projectx", 10 * 60),
+            ("end", 10 * 60 + 30),
+        ]);
+        let tagged = sheet.tagged_times_at(&at(0));
+        assert_eq!(tagged["projectx"], Duration::minutes(90));
+        assert_eq!(tagged["bug"], Duration::minutes(60));
+    }
+
+    #[test]
+    fn pause_matching_ignores_case_and_padding() {
+        assert_eq!(effective("Pause"), "pause");
+        assert_eq!(effective("LUNCH"), "pause");
+        assert_eq!(effective(" break "), "pause");
+        assert_eq!(effective("[ End ]"), "pause");
+        assert_eq!(effective("breakfast"), "breakfast");
+    }
+
+    #[test]
+    fn pause_words_come_from_the_config() {
+        let config = Config {
+            pause_texts: vec!["kaffee".into(), "telefonat-privat".into()],
+            pause_label: "Pause".into(),
+            ..Config::default()
+        };
+        assert_eq!(effective_text("Kaffee".into(), &config), "Pause");
+        assert_eq!(effective_text("Pause".into(), &config), "Pause");
+        assert_eq!(effective_text("lunch".into(), &config), "lunch");
+    }
+
+    #[test]
+    fn sorted_tasks_are_longest_first_with_alphabetical_ties() {
+        let sheet = sheet(&[
+            ("b", 60),
+            ("a", 90),
+            ("pause", 120),
+            ("c", 240),
+            ("end", 300),
+        ]);
+        assert_eq!(
+            sheet.task_lines(true, false),
+            "pause: 2:00\nc: 1:00\na: 0:30\nb: 0:30"
+        );
+    }
+
+    #[test]
+    fn percentages_are_relative_to_the_working_total() {
+        let sheet = sheet(&[
+            ("meeting", 8 * 60),
+            ("pause", 10 * 60),
+            ("coding", 11 * 60),
+            ("end", 14 * 60),
+        ]);
+        assert_eq!(
+            sheet.task_lines(false, true),
+            "coding: 3:00 (60%)\nmeeting: 2:00 (40%)"
+        );
+        assert_eq!(
+            sheet.task_lines(true, true),
+            "coding: 3:00 (60%)\nmeeting: 2:00 (40%)\npause: 1:00"
+        );
+    }
+
+    #[test]
+    fn billable_total_sums_the_rounded_tasks() {
+        let sheet = sheet(&[("a", 0), ("b", 20), ("c", 40), ("d", 55), ("end", 55)]);
+        assert_eq!(
+            sheet.billable_time_by_tasks(),
+            "a: 0:30\nb: 0:30\nc: 0:15\nd: 0:00"
+        );
+        assert_eq!(sheet.billable_sum_as_str(), "1:15");
+        assert_eq!(sheet.sum_as_str(), "0:55");
+    }
+
+    #[test]
+    fn target_status_shows_remaining_time_and_overtime() {
+        let mut sheet = sheet(&[("work", 8 * 60), ("pause", 12 * 60), ("end", 13 * 60)]);
+        assert_eq!(sheet.target_status().as_deref(), Some("4:00 left"));
+        sheet.times.insert(2, TimePoint::new("work", 12 * 60 + 30));
+        sheet.times[3] = TimePoint::new("end", 17 * 60 + 15);
+        assert_eq!(sheet.target_status().as_deref(), Some("+0:45 overtime"));
+    }
+
+    #[test]
+    fn accepts_single_digit_hours_in_clock_form() {
+        assert_eq!(parse_minutes("9:30").unwrap(), 570);
+        assert_eq!(parse_minutes("09:30").unwrap(), 570);
+    }
+
+    #[test]
+    fn rejects_out_of_range_clock_times() {
+        assert!(parse_minutes("25:99").is_err());
+        assert!(parse_minutes("48:00").is_err());
+        assert!(parse_minutes("9:3x").is_err());
+    }
+
+    #[test]
+    fn temp_file_sits_next_to_the_sheet() {
+        assert_eq!(
+            temp_path_for(&PathBuf::from("/data/2026/01/05.json")),
+            PathBuf::from("/data/2026/01/.05.json.tmp")
+        );
+    }
+
+    #[test]
+    fn sorts_entries_out_of_order_on_load() {
+        let dir = std::env::temp_dir().join(format!("tracc-sort-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("05.json");
+        std::fs::write(
+            &path,
+            r#"[{"text": "b", "time": "10:00"}, {"text": "a", "time": "9:00"}]"#,
+        )
+        .unwrap();
+        let config = Rc::new(Config::default());
+        let times = TimeSheet::load(path.clone(), Some(date!(2026 - 01 - 05)), &config).unwrap();
+        assert!(times.reordered_on_load);
+        assert_eq!(times.times[0].time(), 9 * 60);
+        assert!(times
+            .warnings()
+            .contains(&"entries were sorted by time".to_string()));
+
+        times.save().unwrap();
+        let times = TimeSheet::load(path, Some(date!(2026 - 01 - 05)), &config).unwrap();
+        assert!(!times.reordered_on_load);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn takes_the_day_of_a_given_file_from_the_file() {
+        let dir = std::env::temp_dir().join(format!("tracc-given-{}", std::process::id()));
+        let config = Rc::new(Config::default());
+        let dated = dir.join("2026").join("01").join("05.json");
+        std::fs::create_dir_all(dated.parent().unwrap()).unwrap();
+        std::fs::write(&dated, r#"[{"text": "a", "time": [9, 0, 0, 0]}]"#).unwrap();
+        let times = TimeSheet::load(dated, None, &config).unwrap();
+        assert_eq!(times.date, date!(2026 - 01 - 05));
+        assert_eq!(times.times[0].time(), 9 * 60);
+
+        let loose = dir.join("export.json");
+        std::fs::write(
+            &loose,
+            r#"[{"text": "b", "time": "2025-12-24T23:30:00+01:00"},
+                {"text": "a", "time": "2025-12-24T09:00:00+01:00"}]"#,
+        )
+        .unwrap();
+        let times = TimeSheet::load(loose.clone(), None, &config).unwrap();
+        assert_eq!(times.date, date!(2025 - 12 - 24));
+        assert_eq!(times.times[1].time(), 23 * 60 + 30);
+        std::fs::remove_file(&loose).unwrap();
+        let times = TimeSheet::open_file(loose, None, &config).unwrap();
+        assert_eq!(times.date, TimeSheet::current_date());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tells_missing_from_broken_sheets() {
+        use crate::error::TraccError;
+        let dir = std::env::temp_dir().join(format!("tracc-broken-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = Rc::new(Config::default());
+        let date = date!(2026 - 01 - 05);
+        let missing = dir.join("04.json");
+        assert!(matches!(
+            TimeSheet::load(missing.clone(), Some(date), &config),
+            Err(TraccError::NotFound(_))
+        ));
+        assert!(TimeSheet::open_file(missing, Some(date), &config)
+            .unwrap()
+            .times
+            .is_empty());
+        let broken = dir.join("05.json");
+        std::fs::write(&broken, r#"[{"text": "a""#).unwrap();
+        assert!(matches!(
+            TimeSheet::open_file(broken, Some(date), &config),
+            Err(TraccError::Parse(..))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn restores_the_selection_within_range() {
+        let dir = std::env::temp_dir().join(format!("tracc-selection-{}", std::process::id()));
+        let config = Rc::new(Config {
+            timesheet_dir: Some(dir.clone()),
+            ..Config::default()
+        });
+        let mut times = sheet(&[("a", 60), ("b", 120), ("c", 180)]);
+        times.config = Rc::clone(&config);
+        times.path = dir.join("05.json");
+        times.selected = 1;
+        times.remember_selection().unwrap();
+
+        times.selected = 2;
+        times.restore_selection();
+        assert_eq!(times.selected, 1);
+
+        times.times.truncate(1);
+        times.restore_selection();
+        assert_eq!(times.selected, 0);
+
+        let mut other = sheet(&[("a", 60), ("b", 120)]);
+        other.config = config;
+        other.path = dir.join("06.json");
+        other.selected = 1;
+        other.restore_selection();
+        assert_eq!(other.selected, 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn back_up_keeps_only_the_newest_copies() {
+        let dir = std::env::temp_dir().join(format!("tracc-backup-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut sheet = sheet(&[("a", 60)]);
+        sheet.path = dir.join("05.json");
+        std::fs::write(&sheet.path, "[]").unwrap();
+        for stamp in ["20000101-000000", "20000102-000000"] {
+            std::fs::write(dir.join(format!("05.json.{}.bak", stamp)), "[]").unwrap();
+        }
+
+        sheet.back_up(2).unwrap();
+        let mut names: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(names.len(), 3);
+        assert_eq!(names[0], "05.json");
+        assert_eq!(names[1], "05.json.20000102-000000.bak");
+        assert!(names[2].ends_with(".bak"));
+    }
 }
@@ -0,0 +1,176 @@
+use crate::config::Config;
+use crate::timesheet;
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf};
+
+/// The todo list isn’t tied to a day, so there is one file next to the timesheets.
+pub struct Todos {
+    pub path: PathBuf,
+    pub items: Vec<TodoItem>,
+    pub selected: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TodoItem {
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+impl TodoItem {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            done: false,
+        }
+    }
+}
+
+pub fn storage_path(config: &Config) -> PathBuf {
+    timesheet::timesheet_base(config).join("todos.json")
+}
+
+impl Todos {
+    /// A missing file is an empty list.
+    pub fn open(config: &Config) -> io::Result<Self> {
+        let path = storage_path(config);
+        let items = match fs::File::open(&path) {
+            Ok(file) => serde_json::from_reader(io::BufReader::new(file)).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("can’t read {}: {}", path.display(), err),
+                )
+            })?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self {
+            path,
+            items,
+            selected: 0,
+        })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        timesheet::write_json_atomically(&self.path, &self.items)
+    }
+
+    pub fn selected_index(&self) -> Option<usize> {
+        if self.items.is_empty() {
+            None
+        } else {
+            Some(self.selected.min(self.items.len() - 1))
+        }
+    }
+
+    pub fn selected_text(&self) -> Option<String> {
+        self.selected_index()
+            .map(|index| self.items[index].text.clone())
+    }
+
+    /// The list rows, each paired with whether it is done.
+    pub fn printable(&self) -> Vec<(String, bool)> {
+        self.items
+            .iter()
+            .map(|item| {
+                let mark = if item.done { "x" } else { " " };
+                (format!("[{}] {}", mark, item.text), item.done)
+            })
+            .collect()
+    }
+
+    pub fn selection_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn selection_down(&mut self) {
+        self.selected = (self.selected + 1).min(self.items.len().saturating_sub(1));
+    }
+
+    /// Adds an item below the selected one and selects it.
+    pub fn add(&mut self, text: &str) {
+        let index = self.selected_index().map_or(0, |index| index + 1);
+        self.items.insert(index, TodoItem::new(text));
+        self.selected = index;
+    }
+
+    pub fn set_selected_text(&mut self, text: &str) {
+        if let Some(index) = self.selected_index() {
+            self.items[index].text = text.to_string();
+        }
+    }
+
+    pub fn toggle_selected(&mut self) {
+        if let Some(index) = self.selected_index() {
+            self.items[index].done = !self.items[index].done;
+        }
+    }
+
+    pub fn remove_selected(&mut self) {
+        if let Some(index) = self.selected_index() {
+            self.items.remove(index);
+            self.selected = index.min(self.items.len().saturating_sub(1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn todos(texts: &[&str]) -> Todos {
+        Todos {
+            path: PathBuf::new(),
+            items: texts.iter().map(|text| TodoItem::new(text)).collect(),
+            selected: 0,
+        }
+    }
+
+    #[test]
+    fn adds_below_the_selection() {
+        let mut list = todos(&[]);
+        list.add("first");
+        list.add("second");
+        list.selection_up();
+        list.add("between");
+        let texts: Vec<_> = list.items.iter().map(|item| item.text.as_str()).collect();
+        assert_eq!(texts, vec!["first", "between", "second"]);
+        assert_eq!(list.selected_text().as_deref(), Some("between"));
+    }
+
+    #[test]
+    fn toggles_and_removes() {
+        let mut list = todos(&["a", "b"]);
+        list.selection_down();
+        list.toggle_selected();
+        assert_eq!(
+            list.printable(),
+            vec![("[ ] a".to_string(), false), ("[x] b".to_string(), true)]
+        );
+        list.remove_selected();
+        list.remove_selected();
+        list.remove_selected();
+        assert_eq!(list.selected_index(), None);
+    }
+
+    #[test]
+    fn done_defaults_to_false_when_reading() {
+        let items: Vec<TodoItem> = serde_json::from_str(r#"[{"text": "call"}]"#).unwrap();
+        assert_eq!(items, vec![TodoItem::new("call")]);
+    }
+
+    #[test]
+    fn names_a_broken_file() {
+        let dir = std::env::temp_dir().join(format!("tracc-todos-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = Config {
+            timesheet_dir: Some(dir.clone()),
+            ..Config::default()
+        };
+        std::fs::write(dir.join("todos.json"), "[{").unwrap();
+        let err = Todos::open(&config).err().unwrap();
+        assert!(err.to_string().starts_with("can’t read "));
+        assert!(err.to_string().contains("todos.json"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
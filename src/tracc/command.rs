@@ -0,0 +1,96 @@
+use super::input::InputState;
+use super::Tracc;
+use crate::timesheet;
+use std::{fs, io, path::PathBuf};
+
+/// A line typed after `:`.
+enum Command {
+    Write,
+    Quit,
+    WriteQuit,
+    Export(ExportFormat, PathBuf),
+    /// The daily target in minutes, for the rest of the session.
+    Target(i64),
+}
+
+enum ExportFormat {
+    Csv,
+    Markdown,
+    Ics,
+    Json,
+    JsonLines,
+}
+
+const EXPORT_USAGE: &str = "usage: :export csv|md|ics|json|jsonl <path>";
+
+fn parse(line: &str) -> Result<Command, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        ["w"] => Ok(Command::Write),
+        ["q"] => Ok(Command::Quit),
+        ["wq" | "x"] => Ok(Command::WriteQuit),
+        ["export", format, path @ ..] if !path.is_empty() => {
+            let format = match *format {
+                "csv" => ExportFormat::Csv,
+                "md" => ExportFormat::Markdown,
+                "ics" => ExportFormat::Ics,
+                "json" => ExportFormat::Json,
+                "jsonl" => ExportFormat::JsonLines,
+                _ => return Err(EXPORT_USAGE.to_string()),
+            };
+            Ok(Command::Export(format, PathBuf::from(path.join(" "))))
+        }
+        ["export", ..] => Err(EXPORT_USAGE.to_string()),
+        ["target", value] => timesheet::parse_minutes(value).map(Command::Target),
+        ["target", ..] => Err("usage: :target H:MM".to_string()),
+        _ => Err(format!("unknown command: {}", line.trim())),
+    }
+}
+
+impl Tracc {
+    /// Runs a `:` command line; problems end up in the status line.
+    pub(crate) fn run_command(&mut self, line: &str) -> Result<InputState, io::Error> {
+        if line.trim().is_empty() {
+            return Ok(InputState::Normal);
+        }
+        let command = match parse(line) {
+            Ok(command) => command,
+            Err(err) => {
+                self.set_status(err);
+                return Ok(InputState::Normal);
+            }
+        };
+        match command {
+            Command::Write => self.persist_state(),
+            Command::Quit => return Ok(InputState::Quit),
+            Command::WriteQuit => {
+                self.persist_state();
+                return Ok(InputState::Quit);
+            }
+            Command::Export(format, path) => {
+                let content = match format {
+                    ExportFormat::Csv => self.times.to_csv(),
+                    ExportFormat::Markdown => self.times.to_markdown(),
+                    ExportFormat::Ics => self.times.to_ics(),
+                    ExportFormat::Json => self.times.summary_json().to_string(),
+                    ExportFormat::JsonLines => self.times.to_jsonl(),
+                };
+                match fs::write(&path, content) {
+                    Ok(()) => self.set_status(format!("exported to {}", path.display())),
+                    Err(err) => self.set_status(format!("can’t write {}: {}", path.display(), err)),
+                }
+            }
+            Command::Target(minutes) => {
+                let mut config = (*self.config).clone();
+                config.daily_target = minutes;
+                self.replace_config(config);
+                self.set_status(format!(
+                    "daily target is {}:{:02} for this session",
+                    minutes / 60,
+                    minutes % 60
+                ));
+            }
+        }
+        Ok(InputState::Normal)
+    }
+}
@@ -5,12 +5,34 @@ use ratatui::layout::Rect;
 use std::convert::TryFrom;
 use time::{Date, Month};
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub(crate) enum EditKind {
     Text(usize),
     Time(usize),
-    NewAt { index: usize, time: i64 },
+    NewAt {
+        index: usize,
+        time: i64,
+        seconds: i64,
+    },
+    NewTime(usize),
+    SplitAt(usize),
+    /// Asks which text to rename; `effective` matches tags and overrides too.
+    RenameFrom {
+        index: usize,
+        effective: bool,
+    },
+    RenameTo {
+        index: usize,
+        from: String,
+        effective: bool,
+    },
+    NewTodo,
+    TodoText(usize),
     LoadDay(usize),
+    Search(usize),
+    /// A `:` command line, shown in the status line.
+    Command(usize),
+    Project(usize),
 }
 
 #[derive(Copy, Clone)]
@@ -20,6 +42,22 @@ pub(crate) enum EditOutcome {
     Cancel,
 }
 
+/// Tab cycles through the entry texts matching what was typed before the first Tab.
+pub(crate) struct Completion {
+    typed: String,
+    matches: Vec<String>,
+    next: usize,
+}
+
+impl Completion {
+    /// The next match, and after the last one the typed text again.
+    fn advance(&mut self) -> &str {
+        let text = self.matches.get(self.next).unwrap_or(&self.typed);
+        self.next = (self.next + 1) % (self.matches.len() + 1);
+        text
+    }
+}
+
 pub(crate) struct EditState {
     pub(crate) kind: EditKind,
     pub(crate) text: String,
@@ -79,8 +117,90 @@ impl EditState {
     }
 
     pub(crate) fn new_at(index: usize, time: i64) -> Self {
+        Self::new_at_clock(index, time, 0)
+    }
+
+    pub(crate) fn new_at_clock(index: usize, time: i64, seconds: i64) -> Self {
+        Self {
+            kind: EditKind::NewAt {
+                index,
+                time,
+                seconds,
+            },
+            text: String::new(),
+            cursor: 0,
+        }
+    }
+
+    /// Asks for the time of a new item before asking for its text.
+    pub(crate) fn new_time(index: usize, time: i64) -> Self {
+        let text = format_time(time);
+        let cursor = text.len();
+        Self {
+            kind: EditKind::NewTime(index),
+            text,
+            cursor,
+        }
+    }
+
+    /// Asks where to split the entry at `index`; its text comes next.
+    pub(crate) fn split_at(index: usize, time: i64) -> Self {
+        let text = format_time(time);
+        let cursor = text.len();
+        Self {
+            kind: EditKind::SplitAt(index),
+            text,
+            cursor,
+        }
+    }
+
+    pub(crate) fn rename(index: usize, text: String, effective: bool) -> Self {
+        let cursor = text.len();
+        Self {
+            kind: EditKind::RenameFrom { index, effective },
+            text,
+            cursor,
+        }
+    }
+
+    pub(crate) fn new_todo() -> Self {
+        Self {
+            kind: EditKind::NewTodo,
+            text: String::new(),
+            cursor: 0,
+        }
+    }
+
+    pub(crate) fn todo_text(index: usize, text: String) -> Self {
+        let cursor = text.len();
+        Self {
+            kind: EditKind::TodoText(index),
+            text,
+            cursor,
+        }
+    }
+
+    pub(crate) fn search(index: usize, term: String) -> Self {
+        let cursor = term.len();
+        Self {
+            kind: EditKind::Search(index),
+            text: term,
+            cursor,
+        }
+    }
+
+    pub(crate) fn project(index: usize, project: String) -> Self {
+        let cursor = project.len();
+        Self {
+            kind: EditKind::Project(index),
+            text: project,
+            cursor,
+        }
+    }
+
+    pub(crate) fn command(index: usize) -> Self {
         Self {
-            kind: EditKind::NewAt { index, time },
+            kind: EditKind::Command(index),
             text: String::new(),
             cursor: 0,
         }
@@ -109,9 +229,7 @@ impl EditState {
             KeyCode::Delete if input.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.delete_next_word()
             }
-            KeyCode::Char('d') if input.modifiers == KeyModifiers::ALT => {
-                self.delete_next_word()
-            }
+            KeyCode::Char('d') if input.modifiers == KeyModifiers::ALT => self.delete_next_word(),
             KeyCode::Backspace => self.backspace(),
             KeyCode::Delete => self.delete_next_char(),
             KeyCode::Left if input.modifiers.contains(KeyModifiers::CONTROL) => {
@@ -135,23 +253,65 @@ impl EditState {
             EditKind::Text(_) => " edit item ",
             EditKind::Time(_) => " edit time ",
             EditKind::NewAt { .. } => " new item ",
+            EditKind::NewTime(_) => " new item at ",
+            EditKind::SplitAt(_) => " split at ",
+            EditKind::RenameFrom { .. } => " rename all ",
+            EditKind::RenameTo { .. } => " rename to ",
+            EditKind::NewTodo => " new todo ",
+            EditKind::TodoText(_) => " edit todo ",
             EditKind::LoadDay(_) => " load date ",
+            EditKind::Search(_) => " search ",
+            EditKind::Command(_) => " command ",
+            EditKind::Project(_) => " project (empty clears) ",
         }
     }
 
+    /// Whether this edits an entry text, where Tab completes and `Ctrl+o` starts the next entry.
+    pub(crate) fn completes_text(&self) -> bool {
+        matches!(self.kind, EditKind::Text(_) | EditKind::NewAt { .. })
+    }
+
+    /// Committing this would leave an existing entry without text.
+    pub(crate) fn clears_text(&self) -> bool {
+        matches!(self.kind, EditKind::Text(_)) && timesheet::is_empty_input(&self.text)
+    }
+
     pub(crate) fn anchor(&self) -> usize {
         match self.kind {
             EditKind::Text(index) => index,
             EditKind::Time(index) => index,
             EditKind::NewAt { index, .. } => index,
+            EditKind::NewTime(index) => index,
+            EditKind::SplitAt(index) => index,
+            EditKind::RenameFrom { index, .. } => index,
+            EditKind::RenameTo { index, .. } => index,
+            EditKind::NewTodo => 0,
+            EditKind::TodoText(index) => index,
             EditKind::LoadDay(index) => index,
+            EditKind::Search(index) => index,
+            EditKind::Command(index) => index,
+            EditKind::Project(index) => index,
         }
     }
 
-    pub(crate) fn popup_area(&self, frame_area: Rect, list_area: Rect) -> Rect {
+    pub(crate) fn popup_area(&self, frame_area: Rect, list_area: Rect, list_offset: usize) -> Rect {
         match self.kind {
             EditKind::LoadDay(_) => render::centered_area(frame_area, 13, 3),
-            _ => render::edit_area(frame_area, list_area, self.anchor()),
+            // The status line right below the frame area, after the `:`.
+            EditKind::Command(_) => Rect::new(
+                frame_area.x,
+                frame_area.y + frame_area.height,
+                frame_area.width,
+                1,
+            ),
+            EditKind::NewTodo | EditKind::TodoText(_) => {
+                render::centered_area(frame_area, list_area.width.max(40), 3)
+            }
+            _ => render::edit_area(
+                frame_area,
+                list_area,
+                self.anchor().saturating_sub(list_offset),
+            ),
         }
     }
 
@@ -220,6 +380,17 @@ impl EditState {
 }
 
 impl Tracc {
+    pub(crate) fn complete_text(&mut self, edit: &mut EditState) {
+        let times = &self.times;
+        let completion = self.completion.get_or_insert_with(|| Completion {
+            typed: edit.text.clone(),
+            matches: times.completions(&edit.text),
+            next: 0,
+        });
+        edit.text = completion.advance().to_string();
+        edit.cursor = edit.text.len();
+    }
+
     pub(crate) fn commit_edit(
         &mut self,
         edit: EditState,
@@ -230,39 +401,143 @@ impl Tracc {
             EditKind::Text(index) => {
                 self.record_change_snapshot();
                 self.times.selected = index;
-                if text.is_empty() {
+                if timesheet::is_empty_input(&text) {
                     self.times.remove_current();
                 } else {
-                    self.times.set_selected_text(text);
+                    self.times.set_selected_input(&text);
                 }
                 self.persist_state();
                 Ok(None)
             }
-            EditKind::Time(index) => match timesheet::parse_minutes(&text) {
-                Ok(time) => {
+            EditKind::Time(index) => {
+                match self.times.parse_time_from(&text, index.checked_sub(1)) {
+                    Ok(time) if !self.refuses_future(time) => {
+                        self.record_change_snapshot();
+                        self.times.set_selected_time(time);
+                        self.persist_state();
+                        Ok(None)
+                    }
+                    _ => Ok(Some(EditState {
+                        kind: EditKind::Time(index),
+                        text,
+                        cursor,
+                    })),
+                }
+            }
+            EditKind::NewAt {
+                index,
+                time,
+                seconds,
+            } => {
+                if timesheet::is_empty_input(&text) {
+                    self.persist_state();
+                    Ok(None)
+                } else {
                     self.record_change_snapshot();
-                    self.times.set_selected_time(time);
+                    let item = TimePoint::from_input(&text, time).with_seconds(seconds);
+                    self.times.insert_at(item, index);
                     self.persist_state();
                     Ok(None)
                 }
-                Err(_) => Ok(Some(EditState {
-                    kind: EditKind::Time(index),
+            }
+            EditKind::NewTime(index) => match self.times.parse_time_from(&text, Some(index)) {
+                Ok(time) if !self.refuses_future(time) => Ok(Some(EditState::new_at(
+                    self.times.insertion_index_for(time),
+                    time,
+                ))),
+                _ => Ok(Some(EditState {
+                    kind: EditKind::NewTime(index),
+                    text,
+                    cursor,
+                })),
+            },
+            EditKind::SplitAt(index) => match self.times.parse_time_from(&text, Some(index)) {
+                Ok(time) if self.times.can_split_at(time) && !self.refuses_future(time) => {
+                    Ok(Some(EditState::new_at(index + 1, time)))
+                }
+                _ => Ok(Some(EditState {
+                    kind: EditKind::SplitAt(index),
                     text,
                     cursor,
                 })),
             },
-            EditKind::NewAt { index, time } => {
+            EditKind::RenameFrom { .. } if text.is_empty() => Ok(None),
+            EditKind::RenameFrom { index, effective } => {
+                let cursor = text.len();
+                Ok(Some(EditState {
+                    kind: EditKind::RenameTo {
+                        index,
+                        from: text.clone(),
+                        effective,
+                    },
+                    text,
+                    cursor,
+                }))
+            }
+            EditKind::RenameTo {
+                index,
+                from,
+                effective,
+            } => {
                 if text.is_empty() {
+                    return Ok(Some(EditState {
+                        kind: EditKind::RenameTo {
+                            index,
+                            from,
+                            effective,
+                        },
+                        text,
+                        cursor,
+                    }));
+                }
+                let mut renamed = self.times.clone();
+                if renamed.rename_all(&from, &text, effective) > 0 {
+                    self.record_change_snapshot();
+                    self.times = renamed;
                     self.persist_state();
-                    Ok(None)
-                } else {
+                }
+                Ok(None)
+            }
+            EditKind::NewTodo => {
+                if !text.trim().is_empty() {
+                    self.todos.add(&text);
+                    self.persist_todos();
+                }
+                Ok(None)
+            }
+            EditKind::TodoText(index) => {
+                if text.trim().is_empty() {
+                    return Ok(Some(EditState {
+                        kind: EditKind::TodoText(index),
+                        text,
+                        cursor,
+                    }));
+                }
+                self.todos.selected = index;
+                self.todos.set_selected_text(&text);
+                self.persist_todos();
+                Ok(None)
+            }
+            EditKind::Search(_) if text.is_empty() => {
+                self.search = None;
+                Ok(None)
+            }
+            EditKind::Search(_) => {
+                self.times.select_best_match(&text);
+                self.search = Some(text);
+                Ok(None)
+            }
+            EditKind::Project(index) => {
+                self.times.selected = index;
+                if self.times.selected_project().unwrap_or_default() != text.trim() {
                     self.record_change_snapshot();
-                    let item = TimePoint::new(&text, time);
-                    self.times.insert_at(item, index);
+                    self.times.set_selected_project(&text);
                     self.persist_state();
-                    Ok(None)
                 }
+                Ok(None)
             }
+            // Run by `handle_edit_input`, since a command can quit.
+            EditKind::Command(_) => Ok(None),
             EditKind::LoadDay(index) => match parse_date(&text) {
                 Ok(date) => {
                     self.load_day(date)?;
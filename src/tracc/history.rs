@@ -1,30 +1,34 @@
 use super::Tracc;
-use crate::timesheet;
-use std::{fs, io::Write};
 
 impl Tracc {
-    pub(crate) fn persist_state(&self) {
-        fn write(path: &std::path::Path, content: &str) {
-            if let Some(parent) = path.parent() {
-                fs::create_dir_all(parent).ok();
+    pub(crate) fn persist_state(&mut self) {
+        if self.backed_up.insert(self.times.path.clone()) {
+            // A failed backup shouldn’t keep the actual data from being saved.
+            self.times.back_up(self.config.backups).ok();
+        }
+        if let Err(err) = self.times.save() {
+            panic!(
+                "Can’t save state to {}: {}. Dumping raw data:\n{}",
+                self.times.path.display(),
+                err,
+                self.times.to_json().unwrap()
+            );
+        }
+        // The file on disk is in order now.
+        self.times.reordered_on_load = false;
+        if std::mem::take(&mut self.dirty) {
+            if self.times.times.is_empty() {
+                self.changed_sheets.remove(&self.times.path);
+            } else {
+                let sheet = self.times.clone();
+                self.changed_sheets.insert(sheet.path.clone(), sheet);
             }
-            std::fs::OpenOptions::new()
-                .create(true)
-                .write(true)
-                .truncate(true)
-                .open(path)
-                .ok()
-                .unwrap_or_else(|| {
-                    panic!("Can’t save state to JSON. Dumping raw data:\n{}", content)
-                })
-                .write_all(content.as_bytes())
-                .unwrap();
         }
-        let times_ser = serde_json::to_string(&self.times.times).unwrap();
-        write(&timesheet::storage_path_for(self.times.date), &times_ser);
+        self.set_status("saved");
     }
 
     pub(crate) fn record_change_snapshot(&mut self) {
+        self.dirty = true;
         self.redo_history.clear();
         self.push_history();
     }
@@ -45,6 +49,7 @@ impl Tracc {
         self.redo_history.push_back(current);
 
         self.times = previous;
+        self.dirty = true;
         self.input_state = super::input::InputState::Normal;
         self.sheet_locked = !self.times.is_today() && self.undo_history.is_empty();
         self.persist_state();
@@ -56,10 +61,9 @@ impl Tracc {
             return Ok(());
         };
 
-        let current = self.times.clone();
-        self.undo_history.push_back(current);
-
+        self.push_history();
         self.times = next;
+        self.dirty = true;
         self.input_state = super::input::InputState::Normal;
         self.persist_state();
         self.terminal.hide_cursor()
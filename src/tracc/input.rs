@@ -1,11 +1,12 @@
-use super::edit::{EditOutcome, EditState};
+use super::edit::{EditKind, EditOutcome, EditState};
 use super::navigation::PendingAction;
 use super::Tracc;
 use crate::confirm::ConfirmChoice;
+use crate::timesheet::{self, TimePoint};
 use crossterm::event::{
     Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
 };
-use std::io;
+use std::{io, time::Instant};
 
 pub(crate) enum InputState {
     Normal,
@@ -13,6 +14,8 @@ pub(crate) enum InputState {
     Confirm(ConfirmState),
     Prefix(PrefixState),
     Help,
+    /// Selecting the entries between the anchor and the selection for a `d`, `<` or `>`.
+    Visual(usize),
     Quit,
 }
 
@@ -29,8 +32,24 @@ pub(crate) struct ConfirmState {
 
 impl Tracc {
     pub(crate) fn handle_input(&mut self, input: Event) -> Result<(), io::Error> {
-        match input {
-            Event::Key(input) => {
+        let idle_pause = self.idle_pause();
+        if matches!(input, Event::Key(_) | Event::Mouse(_)) {
+            self.last_input = Instant::now();
+        }
+        match (input, idle_pause) {
+            // The key that ends the idle time only brings up the question.
+            (Event::Key(_), Some(pause)) => {
+                let since = timesheet::format_time_of_day(pause.time(), &self.config.time_format)
+                    .unwrap_or_default();
+                self.input_state = InputState::Confirm(ConfirmState {
+                    message: format!("No input since {}. Insert a pause there?", since),
+                    action: PendingAction::InsertPause(pause),
+                    selected: ConfirmChoice::Yes,
+                });
+                self.set_status("idle detected");
+            }
+            (Event::Key(input), _) => {
+                self.status = None;
                 let state = std::mem::replace(&mut self.input_state, InputState::Normal);
                 self.input_state = match state {
                     InputState::Normal => self.handle_normal_input(input)?,
@@ -38,32 +57,77 @@ impl Tracc {
                     InputState::Confirm(confirm) => self.handle_confirm_input(confirm, input)?,
                     InputState::Prefix(prefix) => self.handle_prefix_input(prefix, input)?,
                     InputState::Help => self.handle_help_input(input),
+                    InputState::Visual(anchor) => self.handle_visual_input(anchor, input)?,
                     InputState::Quit => InputState::Quit,
                 };
             }
-            Event::Mouse(mouse) => self.handle_mouse_input(mouse)?,
+            (Event::Mouse(mouse), _) => self.handle_mouse_input(mouse)?,
             _ => {}
         }
         Ok(())
     }
 
+    /**
+     * A pause entry at the last input, if there was none for `idle_minutes`
+     * while a task kept running on today's sheet.
+     */
+    fn idle_pause(&self) -> Option<TimePoint> {
+        let threshold = self.config.idle_minutes * 60;
+        let idle = self.last_input.elapsed().as_secs();
+        if threshold == 0
+            || idle < threshold
+            || !matches!(self.input_state, InputState::Normal)
+            || !self.times.is_today()
+            || self.times.current_task().is_none()
+        {
+            return None;
+        }
+        let now = self.times.now_point(&self.config.pause_label);
+        let start = now.time() * 60 + now.seconds() - idle as i64;
+        let seconds = if self.config.seconds { start % 60 } else { 0 };
+        let pause = TimePoint::new(&self.config.pause_label, start / 60).with_seconds(seconds);
+        // An entry made while idle, e.g. by the Pomodoro timer, already ended the task.
+        let after_last = self
+            .times
+            .times
+            .last()
+            .is_none_or(|last| last.time() < pause.time());
+        (start >= 0 && after_last).then_some(pause)
+    }
+
     fn handle_mouse_input(&mut self, mouse: MouseEvent) -> Result<(), io::Error> {
-        if !matches!(mouse.kind, MouseEventKind::Down(MouseButton::Left)) {
-            return Ok(());
+        let over_list = super::render::contains(self.list_area, mouse.column, mouse.row);
+        match mouse.kind {
+            MouseEventKind::ScrollDown if over_list => {
+                if matches!(self.input_state, InputState::Normal) {
+                    self.times.selection_down();
+                }
+                return Ok(());
+            }
+            MouseEventKind::ScrollUp if over_list => {
+                if matches!(self.input_state, InputState::Normal) {
+                    self.times.selection_up();
+                }
+                return Ok(());
+            }
+            MouseEventKind::Down(MouseButton::Left) => {}
+            _ => return Ok(()),
         }
 
         match &mut self.input_state {
-            InputState::Normal => {
+            InputState::Normal if over_list => {
                 if let Some(index) = super::render::list_index_for_click(
                     self.list_area,
                     mouse.row,
+                    self.list_state.offset(),
                     self.times.times.len(),
                 ) {
                     self.times.selected = index;
                 }
             }
             InputState::Editing(edit) => {
-                let popup_area = edit.popup_area(self.frame_area, self.list_area);
+                let popup_area =
+                    edit.popup_area(self.frame_area, self.list_area, self.list_state.offset());
                 if super::render::contains(popup_area, mouse.column, mouse.row) {
                     edit.cursor =
                         super::render::cursor_for_click(&edit.text, mouse.column, popup_area.x + 1);
@@ -75,9 +139,48 @@ impl Tracc {
         Ok(())
     }
 
+    /// Swaps a key from the `keys` config for the built-in key of its action.
+    fn remapped(&self, mut input: KeyEvent) -> KeyEvent {
+        if let KeyCode::Char(c) = input.code {
+            if !input
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+            {
+                input.code = KeyCode::Char(self.config.keys.translate(c));
+            }
+        }
+        input
+    }
+
     fn handle_normal_input(&mut self, input: KeyEvent) -> Result<InputState, io::Error> {
-        match input.code {
+        if self.todos_focused {
+            return self.handle_todo_input(input);
+        }
+        match self.remapped(input).code {
             KeyCode::Char('q') => Ok(InputState::Quit),
+            KeyCode::Char('w') => {
+                self.persist_state();
+                Ok(InputState::Normal)
+            }
+            KeyCode::Char(c @ ('d' | 'u' | 'f' | 'b'))
+                if input.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                let page = self.list_page_height();
+                let rows = match c {
+                    'd' => page / 2,
+                    'u' => -(page / 2),
+                    'f' => page,
+                    _ => -page,
+                };
+                self.times.selection_by(rows);
+                Ok(InputState::Normal)
+            }
+            KeyCode::Char('j') if input.modifiers.contains(KeyModifiers::ALT) => {
+                self.guard_mutation(PendingAction::MoveDown, self.timesheet_change_message())
+            }
+            KeyCode::Char('k') if input.modifiers.contains(KeyModifiers::ALT) => {
+                self.guard_mutation(PendingAction::MoveUp, self.timesheet_change_message())
+            }
             KeyCode::Char('j') => {
                 self.times.selection_down();
                 Ok(InputState::Normal)
@@ -103,10 +206,18 @@ impl Tracc {
                 self.times.yank();
                 Ok(InputState::Normal)
             }
+            KeyCode::Char('Y') => {
+                match crate::clipboard::copy(&self.times.plain_summary()) {
+                    Ok(()) => self.set_status("copied summary"),
+                    Err(err) => self.set_status(format!("can’t copy the summary: {}", err)),
+                }
+                Ok(InputState::Normal)
+            }
             KeyCode::Char('o') => self.begin_new_item(),
+            KeyCode::Char('O') => self.begin_new_item_at_time(),
             KeyCode::Char('a') => {
                 let selected = self.times.selected;
-                if let Some(text) = self.times.selected_text() {
+                if let Some(text) = self.times.selected_input_text() {
                     self.guard_mutation(
                         PendingAction::BeginEdit(EditState::text(selected, text)),
                         self.timesheet_change_message(),
@@ -117,7 +228,7 @@ impl Tracc {
             }
             KeyCode::Char('i') => {
                 let selected = self.times.selected;
-                if let Some(text) = self.times.selected_text() {
+                if let Some(text) = self.times.selected_input_text() {
                     self.guard_mutation(
                         PendingAction::BeginEdit(EditState::text_at_start(selected, text)),
                         self.timesheet_change_message(),
@@ -151,13 +262,32 @@ impl Tracc {
             KeyCode::Char(' ') => Ok(InputState::Normal),
             KeyCode::Char('?') => Ok(InputState::Help),
             KeyCode::Char('-') => self.guard_mutation(
-                PendingAction::ShiftCurrent(-1),
+                // -1 plus rounding lands on the previous mark; without rounding it needs a full step.
+                PendingAction::ShiftCurrent(if self.config.seconds {
+                    -self.config.shift_step
+                } else {
+                    -1
+                }),
                 self.timesheet_change_message(),
             ),
             KeyCode::Char('+') => self.guard_mutation(
-                PendingAction::ShiftCurrent(5),
+                PendingAction::ShiftCurrent(self.config.shift_step),
+                self.timesheet_change_message(),
+            ),
+            KeyCode::Char('<') => self.guard_mutation(
+                PendingAction::ShiftFollowing(-self.config.shift_step),
+                self.timesheet_change_message(),
+            ),
+            KeyCode::Char('>') => self.guard_mutation(
+                PendingAction::ShiftFollowing(self.config.shift_step),
                 self.timesheet_change_message(),
             ),
+            KeyCode::Char('~') => {
+                self.guard_mutation(PendingAction::RoundAll, self.timesheet_change_message())
+            }
+            KeyCode::Char('=') => {
+                self.guard_mutation(PendingAction::SnapToNow, self.timesheet_change_message())
+            }
             KeyCode::Char('d') => self.guard_mutation(
                 PendingAction::RemoveCurrent,
                 format!(
@@ -172,6 +302,98 @@ impl Tracc {
                     self.times.date_label()
                 ),
             ),
+            KeyCode::Char('D') => {
+                self.guard_mutation(PendingAction::Duplicate, self.timesheet_change_message())
+            }
+            KeyCode::Char('P') => self.guard_mutation(
+                PendingAction::PasteInPlace,
+                format!(
+                    "Paste into {} and change its timesheet data?",
+                    self.times.date_label()
+                ),
+            ),
+            KeyCode::Char('T') => {
+                self.toggle_pomodoro();
+                Ok(InputState::Normal)
+            }
+            KeyCode::Char('t') => {
+                self.todos_focused = true;
+                Ok(InputState::Normal)
+            }
+            KeyCode::Tab => {
+                self.switch_sheet(true)?;
+                Ok(InputState::Normal)
+            }
+            KeyCode::BackTab => {
+                self.switch_sheet(false)?;
+                Ok(InputState::Normal)
+            }
+            KeyCode::Char('x') => self.begin_split(),
+            KeyCode::Char('c') => self.begin_rename(false),
+            KeyCode::Char('C') => self.begin_rename(true),
+            KeyCode::Char('m') => {
+                self.guard_mutation(PendingAction::Merge, self.timesheet_change_message())
+            }
+            KeyCode::Char('s') => {
+                self.guard_mutation(PendingAction::ClockOut, self.timesheet_change_message())
+            }
+            KeyCode::Char('B') => {
+                self.guard_mutation(PendingAction::ToggleBreak, self.timesheet_change_message())
+            }
+            KeyCode::Char('.') => self.guard_mutation(
+                PendingAction::RepeatLastTask,
+                self.timesheet_change_message(),
+            ),
+            KeyCode::Char('S') => {
+                self.sort_by_duration = !self.sort_by_duration;
+                Ok(InputState::Normal)
+            }
+            KeyCode::Char('%') => {
+                self.show_percentages = !self.show_percentages;
+                Ok(InputState::Normal)
+            }
+            KeyCode::Char(':') => self.begin_edit(EditState::command(self.times.selected)),
+            KeyCode::Char('V') if !self.times.times.is_empty() => {
+                Ok(InputState::Visual(self.times.selected))
+            }
+            KeyCode::Char('/') => {
+                let term = self.search.clone().unwrap_or_default();
+                self.begin_edit(EditState::search(self.times.selected, term))
+            }
+            KeyCode::Char(c @ ('n' | 'N')) => {
+                if let Some(term) = &self.search {
+                    self.times.select_match(term, c == 'n');
+                }
+                Ok(InputState::Normal)
+            }
+            KeyCode::Esc => {
+                self.search = None;
+                Ok(InputState::Normal)
+            }
+            KeyCode::Char('@') if self.times.selected_index().is_some() => {
+                let selected = self.times.selected;
+                let project = self
+                    .times
+                    .selected_project()
+                    .unwrap_or_default()
+                    .to_string();
+                self.guard_mutation(
+                    PendingAction::BeginEdit(EditState::project(selected, project)),
+                    self.timesheet_change_message(),
+                )
+            }
+            KeyCode::Char('b') => self.guard_mutation(
+                PendingAction::ToggleBillable,
+                self.timesheet_change_message(),
+            ),
+            KeyCode::Char('H') => {
+                self.show_hours = !self.show_hours;
+                Ok(InputState::Normal)
+            }
+            KeyCode::Char('L') => {
+                self.show_durations = !self.show_durations;
+                Ok(InputState::Normal)
+            }
             KeyCode::Char('u') => {
                 self.undo_previous_edit()?;
                 Ok(InputState::Normal)
@@ -224,10 +446,53 @@ impl Tracc {
         }
     }
 
+    /// Rows visible inside the list's borders, at least one.
+    fn list_page_height(&self) -> isize {
+        self.list_area.height.saturating_sub(2).max(1) as isize
+    }
+
     fn handle_help_input(&mut self, _input: KeyEvent) -> InputState {
         InputState::Normal
     }
 
+    fn handle_visual_input(
+        &mut self,
+        anchor: usize,
+        input: KeyEvent,
+    ) -> Result<InputState, io::Error> {
+        let first = anchor.min(self.times.selected);
+        let last = anchor.max(self.times.selected);
+        match self.remapped(input).code {
+            KeyCode::Char('j') => self.times.selection_down(),
+            KeyCode::Char('k') => self.times.selection_up(),
+            KeyCode::Char('G') => self.times.selection_last(),
+            KeyCode::Char('d') => {
+                return self.guard_mutation(
+                    PendingAction::RemoveRange(first, last),
+                    format!(
+                        "Delete {} timesheet entries for {}?",
+                        last - first + 1,
+                        self.times.date_label()
+                    ),
+                );
+            }
+            KeyCode::Char(c @ ('<' | '>')) => {
+                let minutes = if c == '<' {
+                    -self.config.shift_step
+                } else {
+                    self.config.shift_step
+                };
+                return self.guard_mutation(
+                    PendingAction::ShiftRange(first, last, minutes),
+                    self.timesheet_change_message(),
+                );
+            }
+            KeyCode::Esc | KeyCode::Char('V') => return Ok(InputState::Normal),
+            _ => {}
+        }
+        Ok(InputState::Visual(anchor))
+    }
+
     fn handle_confirm_input(
         &mut self,
         mut confirm: ConfirmState,
@@ -253,12 +518,46 @@ impl Tracc {
         mut edit: EditState,
         input: KeyEvent,
     ) -> Result<InputState, io::Error> {
+        if input.code == KeyCode::Tab && edit.completes_text() {
+            self.complete_text(&mut edit);
+            return Ok(InputState::Editing(edit));
+        }
+        self.completion = None;
+        if input.code == KeyCode::Char('o')
+            && input.modifiers.contains(KeyModifiers::CONTROL)
+            && edit.completes_text()
+        {
+            // The typed text is saved first, so nothing gets lost at the switch.
+            if edit.clears_text() {
+                return Ok(InputState::Editing(edit));
+            }
+            return match self.commit_edit(edit)? {
+                Some(edit) => Ok(InputState::Editing(edit)),
+                None => self.begin_new_item(),
+            };
+        }
         match edit.handle_key(input) {
             EditOutcome::Continue => Ok(InputState::Editing(edit)),
             EditOutcome::Cancel => {
                 self.terminal.hide_cursor()?;
                 Ok(InputState::Normal)
             }
+            EditOutcome::Commit if edit.clears_text() => {
+                if !self.config.remove_empty_entries {
+                    return Ok(InputState::Editing(edit));
+                }
+                self.terminal.hide_cursor()?;
+                self.times.selected = edit.anchor();
+                Ok(InputState::Confirm(ConfirmState {
+                    message: "The text is empty. Delete this entry?".to_string(),
+                    action: PendingAction::RemoveCurrent,
+                    selected: ConfirmChoice::No,
+                }))
+            }
+            EditOutcome::Commit if matches!(edit.kind, EditKind::Command(_)) => {
+                self.terminal.hide_cursor()?;
+                self.run_command(&edit.text)
+            }
             EditOutcome::Commit => match self.commit_edit(edit) {
                 Ok(Some(edit)) => Ok(InputState::Editing(edit)),
                 Ok(None) => {
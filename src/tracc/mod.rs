@@ -1,58 +1,202 @@
+mod command;
 mod edit;
 mod history;
 mod input;
 mod navigation;
+mod pomodoro;
 mod render;
+mod sheets;
+mod todos;
 
 use self::input::InputState;
+use crate::config::Config;
 use crate::timesheet::TimeSheet;
+use crate::todos::Todos;
 use crossterm::event;
 use ratatui::backend::CrosstermBackend;
 use ratatui::layout::Rect;
-use std::{collections::VecDeque, io};
+use ratatui::widgets::ListState;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs, io,
+    path::PathBuf,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
 pub(crate) type Terminal = ratatui::Terminal<CrosstermBackend<io::Stdout>>;
 
 pub struct Tracc {
     times: TimeSheet,
+    config: Rc<Config>,
     terminal: Terminal,
+    file: Option<PathBuf>,
     input_state: InputState,
+    /// The Tab completion of the text being edited, until another key is pressed.
+    completion: Option<self::edit::Completion>,
     frame_area: Rect,
     list_area: Rect,
+    /// Kept across redraws so the list only scrolls when the selection leaves the view.
+    list_state: ListState,
+    todos: Todos,
+    /// Whether normal-mode keys act on the todo pane instead of the times.
+    todos_focused: bool,
     sheet_locked: bool,
+    sort_by_duration: bool,
+    show_percentages: bool,
+    /// Whether the summary pane shows the worked minutes per hour as a bar chart.
+    show_hours: bool,
+    /// Whether each list row ends with its own duration.
+    show_durations: bool,
+    /// The term `/` searched for; its matches are underlined and `n` / `N` jump between them.
+    search: Option<String>,
     undo_history: VecDeque<TimeSheet>,
     redo_history: VecDeque<TimeSheet>,
+    /// Index into the default sheet followed by the `sheets` setting.
+    active_sheet: usize,
+    /// The other sheets by index, opened the first time they are switched to.
+    parked_sheets: Vec<Option<self::sheets::ParkedSheet>>,
+    /// Sheets that were already backed up in this session.
+    backed_up: HashSet<PathBuf>,
+    /// Whether the shown sheet was changed since it was last saved.
+    dirty: bool,
+    /// Every sheet changed in this session as last saved, by path, for the `daily_log`.
+    changed_sheets: HashMap<PathBuf, TimeSheet>,
+    pomodoro: Option<self::pomodoro::Pomodoro>,
+    /// When the last key or mouse event came in, for `idle_minutes`.
+    last_input: Instant,
+    /// The current date as of the last tick, to notice midnight passing.
+    today: time::Date,
+    /// The working minutes of the days before the open one, for the sparkline, by sheet path.
+    recent_totals: Option<(PathBuf, Vec<u64>)>,
+    /// Feedback like "saved" for the status line, with when it was set.
+    status: Option<(String, Instant)>,
 }
 
 const MAX_NEW_ITEM_MINUTES: i64 = 48 * 60;
-const MAX_UNDO_SNAPSHOTS: usize = 20;
+const MAX_UNDO_SNAPSHOTS: usize = 100;
+/// How often the screen is redrawn without input, so the running task keeps counting.
+const TICK_INTERVAL: Duration = Duration::from_secs(30);
+/// The redraw interval while the Pomodoro countdown is shown.
+const POMODORO_TICK_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a status message stays unless a key clears it first.
+const STATUS_DURATION: Duration = Duration::from_secs(4);
 
 impl Tracc {
-    pub fn new(terminal: Terminal) -> Self {
+    /// Opens today's sheet, or `file` if one was given on the command line.
+    pub fn new(terminal: Terminal, config: Config, file: Option<PathBuf>) -> io::Result<Self> {
+        let config = Rc::new(config);
         let date = TimeSheet::current_date();
-        let times = TimeSheet::open(date);
-        Self {
+        let mut times = match &file {
+            Some(path) => TimeSheet::open_file(path.clone(), None, &config)?,
+            None => TimeSheet::open(date, &config)?,
+        };
+        times.restore_selection();
+        let is_new = !times.path.exists();
+        let todos = Todos::open(&config)?;
+        let mut tracc = Self {
             sheet_locked: !times.is_today(),
+            sort_by_duration: false,
+            show_percentages: false,
+            show_hours: false,
+            show_durations: false,
+            search: None,
             times,
+            config,
             terminal,
+            file,
             input_state: InputState::Normal,
+            completion: None,
             frame_area: Rect::default(),
             list_area: Rect::default(),
+            list_state: ListState::default(),
+            todos,
+            todos_focused: false,
             undo_history: VecDeque::new(),
             redo_history: VecDeque::new(),
+            backed_up: HashSet::new(),
+            dirty: false,
+            changed_sheets: HashMap::new(),
+            active_sheet: 0,
+            parked_sheets: Vec::new(),
+            pomodoro: None,
+            last_input: Instant::now(),
+            status: None,
+            recent_totals: None,
+            today: date,
+        };
+        tracc
+            .parked_sheets
+            .resize_with(tracc.config.sheets.len() + 1, || None);
+        // Write brand-new sheets right away so the file exists from the first run on.
+        if is_new {
+            tracc.persist_state();
         }
+        Ok(tracc)
     }
 
     pub fn run(&mut self) -> Result<(), io::Error> {
         loop {
+            self.tick_pomodoro();
+            self.check_rollover();
             self.refresh()?;
-            let input = event::read()?;
-            self.handle_input(input)?;
+            let tick = if self.pomodoro.is_some() {
+                POMODORO_TICK_INTERVAL
+            } else {
+                TICK_INTERVAL
+            };
+            // Wake up in time to clear the status line.
+            let tick = match &self.status {
+                Some((_, set)) => tick.min(STATUS_DURATION.saturating_sub(set.elapsed())),
+                None => tick,
+            };
+            // poll returns as soon as there is input, so this doesn’t delay key presses.
+            if event::poll(tick)? {
+                let input = event::read()?;
+                self.handle_input(input)?;
+            }
             if matches!(self.input_state, InputState::Quit) {
                 break;
             }
         }
         self.terminal.clear()?;
-        Ok(())
+        self.remember_selections();
+        self.write_daily_log()
+    }
+
+    pub(crate) fn set_status(&mut self, message: impl Into<String>) {
+        self.status = Some((message.into(), Instant::now()));
+    }
+
+    /// The status message, until it is older than `STATUS_DURATION`.
+    pub(crate) fn status_message(&self) -> Option<&str> {
+        self.status
+            .as_ref()
+            .filter(|(_, set)| set.elapsed() < STATUS_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// Every day that was changed in this session and has entries gets a line,
+    /// replacing the one an earlier session wrote for it.
+    fn write_daily_log(&self) -> io::Result<()> {
+        if !self.config.daily_log || self.changed_sheets.is_empty() {
+            return Ok(());
+        }
+        let path = crate::timesheet::daily_log_path(&self.config);
+        let log = match fs::read_to_string(&path) {
+            Ok(log) => log,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+        let mut lines: Vec<String> = self
+            .changed_sheets
+            .values()
+            .map(TimeSheet::daily_log_line)
+            .collect();
+        lines.sort();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, crate::timesheet::merge_daily_log(&log, &lines))
     }
 }
@@ -1,15 +1,37 @@
 use super::edit::EditState;
 use super::input::{ConfirmState, InputState};
 use super::Tracc;
-use crate::timesheet::TimeSheet;
+use crate::confirm::ConfirmChoice;
+use crate::timesheet::{TimePoint, TimeSheet};
 
 pub(crate) enum PendingAction {
     BeginEdit(EditState),
     ShiftCurrent(i64),
+    ShiftFollowing(i64),
+    /// Shifts the entries `first..=last` by the minutes.
+    ShiftRange(usize, usize, i64),
+    RemoveRange(usize, usize),
+    SnapToNow,
     RemoveCurrent,
+    MoveUp,
+    MoveDown,
     Paste,
+    Duplicate,
+    PasteInPlace,
+    ClockOut,
+    InsertPause(TimePoint),
+    RepeatLastTask,
+    ToggleBreak,
+    RoundAll,
+    Merge,
+    ToggleBillable,
+    /// Opens today's sheet, starting it with a "start" entry.
+    RollOver,
 }
 
+/// The entry a new day starts with when tracc rolls over into it.
+const ROLLOVER_TEXT: &str = "start";
+
 impl Tracc {
     pub(crate) fn previous_day(&mut self) -> Result<(), std::io::Error> {
         if let Some(date) = self.times.date.previous_day() {
@@ -29,12 +51,41 @@ impl Tracc {
         Ok(())
     }
 
+    /**
+     * Asks to move on to the new day once the date changes under a sheet that was today's.
+     * Waits while a popup is open, and doesn’t ask again if the answer was no.
+     */
+    pub(crate) fn check_rollover(&mut self) {
+        let today = TimeSheet::current_date();
+        if today == self.today || !matches!(self.input_state, InputState::Normal) {
+            return;
+        }
+        let was_today = self.times.date == self.today;
+        self.today = today;
+        if was_today && self.file.is_none() {
+            self.input_state = InputState::Confirm(ConfirmState {
+                message: format!(
+                    "It’s {} now. Switch to the new day and start it with \"{}\"?",
+                    today, ROLLOVER_TEXT
+                ),
+                action: PendingAction::RollOver,
+                selected: ConfirmChoice::Yes,
+            });
+        }
+    }
+
     pub(crate) fn goto_today(&mut self) -> Result<(), std::io::Error> {
         self.load_day(TimeSheet::current_date())
     }
 
     pub(crate) fn load_day(&mut self, date: time::Date) -> Result<(), std::io::Error> {
-        self.times = TimeSheet::open(date);
+        // A sheet opened from an explicit file has no neighbouring days.
+        if self.file.is_some() {
+            return Ok(());
+        }
+        self.times.remember_selection().ok();
+        self.times = TimeSheet::open_sheet(date, &self.config, self.active_sheet_name())?;
+        self.times.restore_selection();
         self.input_state = InputState::Normal;
         self.undo_history.clear();
         self.redo_history.clear();
@@ -43,14 +94,18 @@ impl Tracc {
     }
 
     pub(crate) fn begin_day_load(&mut self) -> Result<InputState, std::io::Error> {
+        if self.file.is_some() {
+            return Ok(InputState::Normal);
+        }
         let selected = self.times.selected;
         self.begin_edit(EditState::date(selected, self.times.date))
     }
 
     pub(crate) fn begin_new_item(&mut self) -> Result<InputState, std::io::Error> {
         let index = self.times.insertion_index_for_now();
-        let time = self.times.current_minutes_since_start();
-        let edit = EditState::new_at(index, time);
+        let now = self.times.now_point("");
+        let time = now.time();
+        let edit = EditState::new_at_clock(index, time, now.seconds());
 
         if time > super::MAX_NEW_ITEM_MINUTES {
             Ok(InputState::Confirm(ConfirmState {
@@ -67,6 +122,44 @@ impl Tracc {
         }
     }
 
+    pub(crate) fn begin_new_item_at_time(&mut self) -> Result<InputState, std::io::Error> {
+        let edit = EditState::new_time(
+            self.times.selected,
+            self.times.current_minutes_since_start(),
+        );
+        self.guard_mutation(
+            PendingAction::BeginEdit(edit),
+            self.timesheet_change_message(),
+        )
+    }
+
+    pub(crate) fn begin_split(&mut self) -> Result<InputState, std::io::Error> {
+        let (Some(index), Some(time)) = (self.times.selected_index(), self.times.selected_time())
+        else {
+            return Ok(InputState::Normal);
+        };
+        self.guard_mutation(
+            PendingAction::BeginEdit(EditState::split_at(index, time)),
+            self.timesheet_change_message(),
+        )
+    }
+
+    /// Starts renaming every entry that matches the selected one's raw or effective text.
+    pub(crate) fn begin_rename(&mut self, effective: bool) -> Result<InputState, std::io::Error> {
+        let text = if effective {
+            self.times.selected_effective_text()
+        } else {
+            self.times.selected_text()
+        };
+        let Some(text) = text else {
+            return Ok(InputState::Normal);
+        };
+        self.guard_mutation(
+            PendingAction::BeginEdit(EditState::rename(self.times.selected, text, effective)),
+            self.timesheet_change_message(),
+        )
+    }
+
     pub(crate) fn execute_action(
         &mut self,
         action: PendingAction,
@@ -74,18 +167,81 @@ impl Tracc {
         match action {
             PendingAction::BeginEdit(edit) => self.begin_edit(edit),
             PendingAction::ShiftCurrent(minutes) => {
+                if self.times.selected_index().is_some() {
+                    // Rounding would zero the seconds, so they are shifted as they are.
+                    let round_to = if self.config.seconds {
+                        1
+                    } else {
+                        self.config.shift_step
+                    };
+                    let mut shifted = self.times.clone();
+                    shifted.shift_current(minutes, round_to);
+                    if self.refuses_future(shifted.times[shifted.selected].time()) {
+                        return Ok(InputState::Normal);
+                    }
+                    self.record_change_snapshot();
+                    self.times = shifted;
+                    self.persist_state();
+                }
+                Ok(InputState::Normal)
+            }
+            PendingAction::ShiftFollowing(minutes) => {
+                if let Some(selected) = self.times.selected_index() {
+                    if self.refuses_later(selected, self.times.times.len() - 1, minutes) {
+                        return Ok(InputState::Normal);
+                    }
+                    self.record_change_snapshot();
+                    self.times.shift_following(minutes);
+                    self.persist_state();
+                }
+                Ok(InputState::Normal)
+            }
+            PendingAction::SnapToNow => {
                 if self.times.selected_index().is_some() {
                     self.record_change_snapshot();
-                    self.times.shift_current(minutes);
+                    self.times.snap_current_to_now(self.config.shift_step);
                     self.persist_state();
                 }
                 Ok(InputState::Normal)
             }
+            PendingAction::ShiftRange(first, last, minutes) => {
+                if self.refuses_later(first, last, minutes) {
+                    return Ok(InputState::Normal);
+                }
+                self.record_change_snapshot();
+                self.times.shift_range(first, last, minutes);
+                self.persist_state();
+                Ok(InputState::Normal)
+            }
+            PendingAction::RemoveRange(first, last) => {
+                self.record_change_snapshot();
+                self.times.remove_range(first, last);
+                self.persist_state();
+                self.set_status("deleted the selected entries");
+                Ok(InputState::Normal)
+            }
             PendingAction::RemoveCurrent => {
                 if self.times.selected_index().is_some() {
                     self.record_change_snapshot();
                     self.times.remove_current();
                     self.persist_state();
+                    self.set_status("deleted entry");
+                }
+                Ok(InputState::Normal)
+            }
+            PendingAction::MoveUp => {
+                if self.times.selected_index().is_some() {
+                    self.record_change_snapshot();
+                    self.times.move_up();
+                    self.persist_state();
+                }
+                Ok(InputState::Normal)
+            }
+            PendingAction::MoveDown => {
+                if self.times.selected_index().is_some() {
+                    self.record_change_snapshot();
+                    self.times.move_down();
+                    self.persist_state();
                 }
                 Ok(InputState::Normal)
             }
@@ -97,9 +253,106 @@ impl Tracc {
                 }
                 Ok(InputState::Normal)
             }
+            PendingAction::Duplicate => {
+                if self.times.selected_index().is_some() {
+                    self.record_change_snapshot();
+                    self.times.duplicate_selected(self.config.shift_step);
+                    self.persist_state();
+                }
+                Ok(InputState::Normal)
+            }
+            PendingAction::ClockOut => {
+                self.record_change_snapshot();
+                self.times.clock_out();
+                self.persist_state();
+                Ok(InputState::Normal)
+            }
+            PendingAction::InsertPause(pause) => {
+                self.record_change_snapshot();
+                self.times.insert_in_order(pause);
+                self.persist_state();
+                Ok(InputState::Normal)
+            }
+            PendingAction::RepeatLastTask => {
+                if self.times.last_task_text().is_some() {
+                    self.record_change_snapshot();
+                    self.times.repeat_last_task();
+                    self.persist_state();
+                }
+                Ok(InputState::Normal)
+            }
+            PendingAction::ToggleBreak => {
+                let mut toggled = self.times.clone();
+                if toggled.toggle_break() {
+                    self.record_change_snapshot();
+                    self.times = toggled;
+                    self.persist_state();
+                }
+                Ok(InputState::Normal)
+            }
+            PendingAction::RoundAll => {
+                if !self.times.times.is_empty() {
+                    self.record_change_snapshot();
+                    self.times.round_all(self.config.shift_step);
+                    self.persist_state();
+                }
+                Ok(InputState::Normal)
+            }
+            PendingAction::RollOver => {
+                // Every change is saved as it happens, so the old day is already on disk.
+                self.goto_today()?;
+                if self.times.times.is_empty() {
+                    self.record_change_snapshot();
+                    let start = self.times.now_point(ROLLOVER_TEXT);
+                    self.times.insert_in_order(start);
+                    self.persist_state();
+                }
+                Ok(InputState::Normal)
+            }
+            PendingAction::ToggleBillable => {
+                if self.times.selected_index().is_some() {
+                    self.record_change_snapshot();
+                    self.times.toggle_selected_billable();
+                    self.persist_state();
+                }
+                Ok(InputState::Normal)
+            }
+            PendingAction::Merge => {
+                if self.times.mergeable_count() > 0 {
+                    self.record_change_snapshot();
+                    self.times.merge_following();
+                    self.persist_state();
+                }
+                Ok(InputState::Normal)
+            }
+            PendingAction::PasteInPlace => {
+                if self.times.can_paste() {
+                    self.record_change_snapshot();
+                    self.times.paste_in_place();
+                    self.persist_state();
+                }
+                Ok(InputState::Normal)
+            }
         }
     }
 
+    /// Whether `minutes` is refused as a time in the future; says so in the status line.
+    pub(crate) fn refuses_future(&mut self, minutes: i64) -> bool {
+        let refused = !self.config.allow_future_entries && self.times.is_in_future(minutes);
+        if refused {
+            self.set_status("that time is in the future (see allow_future_entries)");
+        }
+        refused
+    }
+
+    /// Whether shifting the entries `first..=last` later by `minutes` would move one into the future.
+    fn refuses_later(&mut self, first: usize, last: usize, minutes: i64) -> bool {
+        let last = last.min(self.times.times.len().saturating_sub(1));
+        let moved = self.times.times.get(first..=last).unwrap_or_default();
+        let latest = moved.iter().map(TimePoint::time).max();
+        minutes > 0 && latest.is_some_and(|latest| self.refuses_future(latest + minutes))
+    }
+
     pub(crate) fn timesheet_change_message(&self) -> String {
         format!("Change timesheet data for {}?", self.times.date_label())
     }
@@ -0,0 +1,119 @@
+use super::Tracc;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// How long the summary border stays highlighted after an interval ends.
+const FLASH_DURATION: Duration = Duration::from_secs(3);
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+/// A running Pomodoro timer. It only lives for the session and is never saved.
+pub(crate) struct Pomodoro {
+    phase: Phase,
+    ends_at: Instant,
+    /// Work intervals finished so far, to know when a long break is due.
+    finished_work: u32,
+    flash_until: Option<Instant>,
+}
+
+impl Tracc {
+    /// Starts a work interval, or stops the timer if one is running.
+    pub(crate) fn toggle_pomodoro(&mut self) {
+        self.pomodoro = match self.pomodoro {
+            Some(_) => None,
+            None => Some(Pomodoro {
+                phase: Phase::Work,
+                ends_at: Instant::now() + self.interval(Phase::Work),
+                finished_work: 0,
+                flash_until: None,
+            }),
+        };
+    }
+
+    /**
+     * Moves to the next interval once the current one is over.
+     * A finished work interval adds a pause entry and a finished break resumes the last task,
+     * but only on today's sheet; past days are never changed behind the user's back.
+     */
+    pub(crate) fn tick_pomodoro(&mut self) {
+        let now = Instant::now();
+        let Some(pomodoro) = self.pomodoro.as_ref() else {
+            return;
+        };
+        if now < pomodoro.ends_at {
+            return;
+        }
+        let mut finished_work = pomodoro.finished_work;
+        let next = if pomodoro.phase == Phase::Work {
+            finished_work += 1;
+            if finished_work % self.config.pomodoro.long_break_every == 0 {
+                Phase::LongBreak
+            } else {
+                Phase::ShortBreak
+            }
+        } else {
+            Phase::Work
+        };
+        let resumable = next != Phase::Work || self.times.last_task_text().is_some();
+        if self.times.is_today() && resumable {
+            self.record_change_snapshot();
+            if next == Phase::Work {
+                self.times.repeat_last_task();
+            } else {
+                self.times.start_break();
+            }
+            self.persist_state();
+        }
+        self.pomodoro = Some(Pomodoro {
+            phase: next,
+            ends_at: now + self.interval(next),
+            finished_work,
+            flash_until: Some(now + FLASH_DURATION),
+        });
+        // The terminal bell; a failed write only loses the sound.
+        let mut stdout = io::stdout();
+        stdout.write_all(b"\x07").and_then(|_| stdout.flush()).ok();
+    }
+
+    /// What the summary shows while the timer runs, e.g. "Pomodoro: work 12:34 left".
+    pub(crate) fn pomodoro_status(&self) -> Option<String> {
+        let pomodoro = self.pomodoro.as_ref()?;
+        let left = pomodoro
+            .ends_at
+            .saturating_duration_since(Instant::now())
+            .as_secs();
+        let phase = match pomodoro.phase {
+            Phase::Work => "work",
+            Phase::ShortBreak => "break",
+            Phase::LongBreak => "long break",
+        };
+        Some(format!(
+            "Pomodoro: {} {}:{:02} left",
+            phase,
+            left / 60,
+            left % 60
+        ))
+    }
+
+    /// Whether an interval has just ended.
+    pub(crate) fn pomodoro_flashing(&self) -> bool {
+        self.pomodoro
+            .as_ref()
+            .and_then(|pomodoro| pomodoro.flash_until)
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    fn interval(&self, phase: Phase) -> Duration {
+        let minutes = match phase {
+            Phase::Work => self.config.pomodoro.work,
+            Phase::ShortBreak => self.config.pomodoro.short_break,
+            Phase::LongBreak => self.config.pomodoro.long_break,
+        };
+        Duration::from_secs(minutes * 60)
+    }
+}
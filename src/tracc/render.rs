@@ -5,57 +5,219 @@ use crate::confirm::{self, ConfirmDialog};
 use crate::help;
 use crate::layout;
 use crate::timesheet::{TimePoint, TimeSheet};
-use ratatui::layout::Rect;
+use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Clear, ListState, Padding, Paragraph, Wrap};
+use ratatui::widgets::{
+    Bar, BarChart, BarGroup, Block, Borders, Clear, Gauge, ListState, Padding, Paragraph,
+    Sparkline, Tabs, Wrap,
+};
 
 impl Tracc {
     pub(crate) fn refresh(&mut self) -> Result<(), std::io::Error> {
         let today = TimeSheet::current_date();
+        let recent_totals = self.recent_totals();
         let headline = self.times_headline(today);
-        let summary_content = format!(
-            "Sum: {}\n{}{}\n\n{}",
+        let mut summary_content = self
+            .pomodoro_status()
+            .map(|status| status + "\n\n")
+            .unwrap_or_default();
+        summary_content += &format!(
+            "Work: {}{}{} | {}{}\n\n{}",
             self.times.sum_as_str(),
+            self.times
+                .target_status()
+                .map(|status| format!(" ({})", status))
+                .unwrap_or_default(),
+            self.times
+                .billable_total_str()
+                .map(|billable| format!(" | billable: {}", billable))
+                .unwrap_or_default(),
             self.times.pause_time(),
-            if self.times.has_time_overflow() {
-                "\ntracking exceeds day"
-            } else {
-                ""
-            },
-            self.times.time_by_tasks()
+            self.times
+                .warnings()
+                .into_iter()
+                .chain(self.times.long_running_warning())
+                .map(|warning| format!("\n{}", warning))
+                .collect::<String>(),
+            self.times
+                .task_lines(self.sort_by_duration, self.show_percentages)
         );
+        for section in [self.times.time_by_tags(), self.times.time_by_project()] {
+            if !section.is_empty() {
+                summary_content += "\n\n";
+                summary_content += &section;
+            }
+        }
+        let summary_border = if self.pomodoro_flashing() {
+            Style::default().fg(self.config.theme.highlight_foreground)
+        } else {
+            Style::default()
+        };
         let summary = Paragraph::new(summary_content)
             .wrap(Wrap { trim: true })
             .block(
                 Block::default()
                     .borders(Borders::ALL)
+                    .border_style(summary_border)
                     .padding(Padding::new(1, 1, 0, 0)),
             );
+        let gauge = self.times.target_ratio().map(|ratio| {
+            // Green once the day is nearly done, so a glance is enough.
+            let color = if ratio >= 0.9 {
+                Color::Green
+            } else {
+                Color::Yellow
+            };
+            Gauge::default()
+                .block(Block::default().borders(Borders::ALL).title(" target "))
+                .gauge_style(Style::default().fg(color))
+                .ratio(ratio.clamp(0.0, 1.0))
+                .label(format!(
+                    "{} — {}",
+                    self.times.sum_as_str(),
+                    self.times.target_status().unwrap_or_default()
+                ))
+        });
         let preview = match &self.input_state {
             InputState::Editing(edit) => match edit.kind {
-                EditKind::NewAt { index, time } => Some((index, TimePoint::new(&edit.text, time))),
+                EditKind::NewAt {
+                    index,
+                    time,
+                    seconds,
+                } => Some((
+                    index,
+                    TimePoint::from_input(&edit.text, time).with_seconds(seconds),
+                )),
                 _ => None,
             },
             _ => None,
         };
         let preview_index = preview.as_ref().map(|(index, _)| *index);
-        let times = self.times.printable_with_preview(preview);
-        let timelist = layout::selectable_list(headline, &times);
-        let mut state = ListState::default();
-        state.select(self.times.selected_index_with_preview(preview_index));
+        let times = self
+            .times
+            .printable_with_preview(preview, self.show_durations);
+        let visual = match self.input_state {
+            InputState::Visual(anchor) => {
+                Some(anchor.min(self.times.selected)..=anchor.max(self.times.selected))
+            }
+            _ => None,
+        };
+        let matches: Vec<usize> = match (&visual, &self.search) {
+            (Some(range), _) => range.clone().collect(),
+            (None, Some(term)) => self
+                .times
+                .matching(term)
+                .into_iter()
+                .map(|index| match preview_index {
+                    Some(preview) if index >= preview => index + 1,
+                    _ => index,
+                })
+                .collect(),
+            (None, None) => Vec::new(),
+        };
+        let non_billable: Vec<usize> = self
+            .times
+            .non_billable()
+            .into_iter()
+            .map(|index| match preview_index {
+                Some(preview) if index >= preview => index + 1,
+                _ => index,
+            })
+            .collect();
+        let long_running = self
+            .times
+            .long_running()
+            .map(|(index, _)| match preview_index {
+                Some(preview) if index >= preview => index + 1,
+                _ => index,
+            });
+        let timelist = layout::selectable_list(
+            headline,
+            &times,
+            &matches,
+            &non_billable,
+            long_running,
+            &self.config.theme,
+        );
+        self.list_state
+            .select(self.times.selected_index_with_preview(preview_index));
+        let todo_rows = self.todos.printable();
+        let todo_title = if self.todos_focused {
+            Line::from(Span::styled(
+                " todos ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ))
+        } else {
+            Line::from(" todos (t) ")
+        };
+        let todo_list =
+            layout::selectable_list(todo_title, &todo_rows, &[], &[], None, &self.config.theme);
+        let mut todo_state = ListState::default();
+        // Only the focused pane shows a selection, so it’s clear where keys go.
+        if self.todos_focused {
+            todo_state.select(self.todos.selected_index());
+        }
         let frame_size = self.terminal.size()?;
         let frame_area = Rect::new(0, 0, frame_size.width, frame_size.height);
-        let chunks = layout::layout(frame_area);
+        let rows = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(frame_area);
+        let (frame_area, status_area) = (rows[0], rows[1]);
+        let status = match &visual {
+            Some(range) => format!("-- VISUAL -- {} selected", range.clone().count()),
+            None => self.status_message().unwrap_or_default().to_string(),
+        };
+        let status = Paragraph::new(status);
+        let (tabs_area, body_area) = if self.has_sheets() {
+            let rows =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).split(frame_area);
+            (Some(rows[0]), rows[1])
+        } else {
+            (None, frame_area)
+        };
+        let tabs = Tabs::new(self.sheet_labels())
+            .select(self.active_sheet)
+            .highlight_style(
+                Style::default()
+                    .fg(self.config.theme.highlight_foreground)
+                    .add_modifier(Modifier::BOLD),
+            );
+        let chunks = layout::layout(body_area, self.config.columns, self.config.orientation);
+        let (gauge_area, summary_area) = if gauge.is_some() {
+            let rows =
+                Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).split(chunks[1]);
+            (rows[0], rows[1])
+        } else {
+            (Rect::default(), chunks[1])
+        };
+        let sparkline = (!recent_totals.is_empty()).then(|| {
+            Sparkline::default()
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!(" last {} days ", recent_totals.len())),
+                )
+                .style(Style::default().fg(self.config.theme.highlight_foreground))
+                .data(&recent_totals)
+        });
+        let (summary_area, sparkline_area) = if sparkline.is_some() {
+            let rows =
+                Layout::vertical([Constraint::Min(0), Constraint::Length(4)]).split(summary_area);
+            (rows[0], rows[1])
+        } else {
+            (summary_area, Rect::default())
+        };
+        let hours_chart = self.show_hours.then(|| self.hours_chart());
+        let (summary_area, hours_area) = if hours_chart.is_some() {
+            let rows =
+                Layout::vertical([Constraint::Min(0), Constraint::Length(8)]).split(summary_area);
+            (rows[0], rows[1])
+        } else {
+            (summary_area, Rect::default())
+        };
         self.frame_area = frame_area;
         self.list_area = chunks[0];
         let edit = match &self.input_state {
-            InputState::Editing(edit) => Some((
-                edit.text.clone(),
-                edit.cursor,
-                edit.popup_title(),
-                edit.popup_area(frame_area, chunks[0]),
-            )),
+            InputState::Editing(edit) => Some(edit),
             _ => None,
         };
         let confirm = match &self.input_state {
@@ -64,17 +226,44 @@ impl Tracc {
         };
         let show_help = matches!(self.input_state, InputState::Help);
 
+        let list_state = &mut self.list_state;
         self.terminal.draw(|frame| {
-            frame.render_stateful_widget(timelist, chunks[0], &mut state);
-            frame.render_widget(summary, chunks[1]);
+            if let Some(tabs_area) = tabs_area {
+                frame.render_widget(tabs, tabs_area);
+            }
+            frame.render_stateful_widget(timelist, chunks[0], list_state);
+            if let Some(gauge) = gauge {
+                frame.render_widget(gauge, gauge_area);
+            }
+            frame.render_widget(summary, summary_area);
+            if let Some(sparkline) = sparkline {
+                frame.render_widget(sparkline, sparkline_area);
+            }
+            if let Some(chart) = hours_chart {
+                frame.render_widget(chart, hours_area);
+            }
+            frame.render_stateful_widget(todo_list, chunks[2], &mut todo_state);
+            frame.render_widget(status, status_area);
 
-            if let Some((text, cursor, title, popup_area)) = edit.as_ref() {
-                let input = Paragraph::new(text.as_str())
-                    .block(Block::default().title(*title).borders(Borders::ALL));
-                frame.render_widget(Clear, *popup_area);
-                frame.render_widget(input, *popup_area);
+            if let Some(edit) = edit.filter(|edit| matches!(edit.kind, EditKind::Command(_))) {
+                let command_area = edit.popup_area(frame_area, chunks[0], list_state.offset());
+                frame.render_widget(Clear, command_area);
+                frame.render_widget(Paragraph::new(format!(":{}", edit.text)), command_area);
+                let cursor_x = (command_area.x + 1 + edit.cursor as u16)
+                    .min(command_area.x + command_area.width.saturating_sub(1));
+                frame.set_cursor_position((cursor_x, command_area.y));
+            } else if let Some(edit) = edit {
+                // Placed after the list so it follows the row at the list's new scroll offset.
+                let popup_area = edit.popup_area(frame_area, chunks[0], list_state.offset());
+                let input = Paragraph::new(edit.text.as_str()).block(
+                    Block::default()
+                        .title(edit.popup_title())
+                        .borders(Borders::ALL),
+                );
+                frame.render_widget(Clear, popup_area);
+                frame.render_widget(input, popup_area);
 
-                let cursor_x = (popup_area.x + 1 + *cursor as u16)
+                let cursor_x = (popup_area.x + 1 + edit.cursor as u16)
                     .min(popup_area.x + popup_area.width.saturating_sub(2));
                 frame.set_cursor_position((cursor_x, popup_area.y + 1));
             }
@@ -92,6 +281,64 @@ impl Tracc {
         Ok(())
     }
 
+    /**
+     * The working minutes of the last `sparkline_days` days of the shown sheet, oldest first.
+     * The earlier days are read once per open day; the open one counts live.
+     * A file given on the command line has no dated neighbours, so it gets none.
+     */
+    fn recent_totals(&mut self) -> Vec<u64> {
+        let days = i64::from(self.config.sparkline_days);
+        if days == 0 || self.file.is_some() {
+            return Vec::new();
+        }
+        let minutes = |sheet: &TimeSheet| sheet.working_total().whole_minutes().max(0) as u64;
+        let cached = matches!(&self.recent_totals, Some((path, _)) if *path == self.times.path);
+        if !cached {
+            // An unreadable day shows up as an empty one rather than hiding the rest.
+            let earlier = (1..days)
+                .rev()
+                .map(|offset| {
+                    let date = self.times.date - time::Duration::days(offset);
+                    TimeSheet::open_sheet(date, &self.config, self.active_sheet_name())
+                        .map_or(0, |sheet| minutes(&sheet))
+                })
+                .collect();
+            self.recent_totals = Some((self.times.path.clone(), earlier));
+        }
+        let mut totals = self
+            .recent_totals
+            .as_ref()
+            .map(|(_, earlier)| earlier.clone())
+            .unwrap_or_default();
+        totals.push(minutes(&self.times));
+        totals
+    }
+
+    /// One bar per hour from 0 to 23, without numbers so all of them fit.
+    fn hours_chart(&self) -> BarChart<'static> {
+        let bars: Vec<Bar> = self
+            .times
+            .minutes_by_hour()
+            .iter()
+            .map(|duration| {
+                Bar::default()
+                    .value(duration.whole_minutes().max(0) as u64)
+                    .text_value(String::new())
+            })
+            .collect();
+        BarChart::default()
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" by hour, 0–23 "),
+            )
+            .bar_width(1)
+            .bar_gap(0)
+            .max(60)
+            .bar_style(Style::default().fg(self.config.theme.highlight_foreground))
+            .data(BarGroup::default().bars(&bars))
+    }
+
     fn times_headline(&self, today: time::Date) -> Line<'static> {
         let mut spans = vec![Span::raw("< ")];
         let weekday = format!("{:?}", self.times.date.weekday());
@@ -112,15 +359,26 @@ impl Tracc {
         } else {
             spans.push(Span::raw(" "));
         }
+        spans.push(Span::raw(format!(" — {}", self.times.sum_as_str())));
+        if let Some(term) = &self.search {
+            let count = self.times.matching(term).len();
+            spans.push(Span::raw(format!(" — /{} ({})", term, count)));
+        }
+        if let Some(task) = self.times.current_task() {
+            spans.push(Span::raw(format!(" — {} ", task)));
+        } else {
+            spans.push(Span::raw(" "));
+        }
         Line::from(spans)
     }
 }
 
-pub(crate) fn edit_area(frame_area: Rect, list_area: Rect, selected: usize) -> Rect {
+/// `row` counts from the first visible row of the list.
+pub(crate) fn edit_area(frame_area: Rect, list_area: Rect, row: usize) -> Rect {
     let height = 3;
     let width = list_area.width.saturating_sub(4).max(20);
     let x = list_area.x + 2;
-    let below_row = list_area.y + 1 + selected as u16 + 1;
+    let below_row = list_area.y + 1 + row as u16 + 1;
     let max_y = frame_area.y + frame_area.height.saturating_sub(height);
     let y = below_row.min(max_y);
 
@@ -136,7 +394,13 @@ pub(crate) fn centered_area(frame_area: Rect, width: u16, height: u16) -> Rect {
     Rect::new(x, y, width, height)
 }
 
-pub(crate) fn list_index_for_click(list_area: Rect, row: u16, len: usize) -> Option<usize> {
+/// `offset` is the index of the first visible row of a scrolled list.
+pub(crate) fn list_index_for_click(
+    list_area: Rect,
+    row: u16,
+    offset: usize,
+    len: usize,
+) -> Option<usize> {
     if len == 0 {
         return None;
     }
@@ -147,7 +411,7 @@ pub(crate) fn list_index_for_click(list_area: Rect, row: u16, len: usize) -> Opt
         return None;
     }
 
-    let index = (row - inner_top) as usize;
+    let index = offset + (row - inner_top) as usize;
     (index < len).then_some(index)
 }
 
@@ -0,0 +1,108 @@
+use super::Tracc;
+use crate::config::Config;
+use crate::timesheet::TimeSheet;
+use std::collections::VecDeque;
+use std::io;
+use std::rc::Rc;
+
+/// A sheet that isn’t shown right now. It keeps its own day, selection and undo history.
+pub(crate) struct ParkedSheet {
+    times: TimeSheet,
+    sheet_locked: bool,
+    undo_history: VecDeque<TimeSheet>,
+    redo_history: VecDeque<TimeSheet>,
+}
+
+/// The label of the default sheet in the tab strip.
+pub(crate) const DEFAULT_SHEET_LABEL: &str = "default";
+
+impl Tracc {
+    /// `None` is the default sheet, everything else the `sheets` setting in order.
+    pub(crate) fn active_sheet_name(&self) -> Option<&str> {
+        self.sheet_name(self.active_sheet)
+    }
+
+    fn sheet_name(&self, index: usize) -> Option<&str> {
+        index
+            .checked_sub(1)
+            .map(|index| self.config.sheets[index].as_str())
+    }
+
+    pub(crate) fn sheet_labels(&self) -> Vec<String> {
+        std::iter::once(DEFAULT_SHEET_LABEL.to_string())
+            .chain(self.config.sheets.iter().cloned())
+            .collect()
+    }
+
+    /// Swaps in a changed config for the rest of the session, in every open sheet and snapshot.
+    pub(crate) fn replace_config(&mut self, config: Config) {
+        let config = Rc::new(config);
+        let parked = self.parked_sheets.iter_mut().flatten().flat_map(|parked| {
+            std::iter::once(&mut parked.times)
+                .chain(parked.undo_history.iter_mut())
+                .chain(parked.redo_history.iter_mut())
+        });
+        for sheet in std::iter::once(&mut self.times)
+            .chain(self.undo_history.iter_mut())
+            .chain(self.redo_history.iter_mut())
+            .chain(parked)
+            .chain(self.changed_sheets.values_mut())
+        {
+            sheet.config = Rc::clone(&config);
+        }
+        self.config = config;
+    }
+
+    /// Remembers the selection of the shown sheet and of every parked one.
+    pub(crate) fn remember_selections(&self) {
+        // Losing a selection isn’t worth failing over.
+        self.times.remember_selection().ok();
+        for parked in self.parked_sheets.iter().flatten() {
+            parked.times.remember_selection().ok();
+        }
+    }
+
+    /// Whether the tab strip is shown and Tab switches sheets.
+    pub(crate) fn has_sheets(&self) -> bool {
+        !self.config.sheets.is_empty() && self.file.is_none()
+    }
+
+    /// Activates the next sheet, or the previous one when `forward` is false.
+    pub(crate) fn switch_sheet(&mut self, forward: bool) -> io::Result<()> {
+        if !self.has_sheets() {
+            return Ok(());
+        }
+        let count = self.config.sheets.len() + 1;
+        let target = if forward {
+            (self.active_sheet + 1) % count
+        } else {
+            (self.active_sheet + count - 1) % count
+        };
+        let parked = match self.parked_sheets[target].take() {
+            Some(parked) => parked,
+            None => {
+                let mut times = TimeSheet::open_sheet(
+                    TimeSheet::current_date(),
+                    &self.config,
+                    self.sheet_name(target),
+                )?;
+                times.restore_selection();
+                ParkedSheet {
+                    sheet_locked: !times.is_today(),
+                    times,
+                    undo_history: VecDeque::new(),
+                    redo_history: VecDeque::new(),
+                }
+            }
+        };
+        let current = ParkedSheet {
+            times: std::mem::replace(&mut self.times, parked.times),
+            sheet_locked: std::mem::replace(&mut self.sheet_locked, parked.sheet_locked),
+            undo_history: std::mem::replace(&mut self.undo_history, parked.undo_history),
+            redo_history: std::mem::replace(&mut self.redo_history, parked.redo_history),
+        };
+        self.parked_sheets[self.active_sheet] = Some(current);
+        self.active_sheet = target;
+        Ok(())
+    }
+}
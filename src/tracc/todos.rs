@@ -0,0 +1,45 @@
+use super::edit::EditState;
+use super::input::InputState;
+use super::Tracc;
+use crossterm::event::{KeyCode, KeyEvent};
+use std::io;
+
+impl Tracc {
+    /// Normal-mode keys while the todo pane has focus.
+    pub(crate) fn handle_todo_input(&mut self, input: KeyEvent) -> Result<InputState, io::Error> {
+        match input.code {
+            KeyCode::Char('q') => return Ok(InputState::Quit),
+            KeyCode::Char('?') => return Ok(InputState::Help),
+            KeyCode::Char('t') | KeyCode::Esc => self.todos_focused = false,
+            KeyCode::Char('j') => self.todos.selection_down(),
+            KeyCode::Char('k') => self.todos.selection_up(),
+            KeyCode::Char('o') => return self.begin_edit(EditState::new_todo()),
+            KeyCode::Char('a') => {
+                if let Some(text) = self.todos.selected_text() {
+                    return self.begin_edit(EditState::todo_text(self.todos.selected, text));
+                }
+            }
+            KeyCode::Char(' ') | KeyCode::Char('x') => {
+                self.todos.toggle_selected();
+                self.persist_todos();
+            }
+            KeyCode::Char('d') => {
+                self.todos.remove_selected();
+                self.persist_todos();
+            }
+            _ => {}
+        }
+        Ok(InputState::Normal)
+    }
+
+    pub(crate) fn persist_todos(&mut self) {
+        if let Err(err) = self.todos.save() {
+            panic!(
+                "Can’t save todos to {}: {}. Dumping raw data:\n{}",
+                self.todos.path.display(),
+                err,
+                serde_json::to_string(&self.todos.items).unwrap()
+            );
+        }
+    }
+}